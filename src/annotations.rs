@@ -0,0 +1,61 @@
+use crate::{ExpressionSources, ResourceChange, SchemaBlock};
+
+/// What is known about a resource change besides its values: the drift
+/// behind it, the provider schema of its attributes and the configuration
+/// expressions that set them.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Annotations<'a> {
+    pub drift: Option<&'a ResourceChange>,
+    pub schema: Option<&'a SchemaBlock>,
+    pub sources: Option<&'a ExpressionSources>,
+}
+
+impl<'a> Annotations<'a> {
+    /// The annotations of the attributes of the `index`th of the `count`
+    /// `name` blocks a change sets. Configured blocks are only matched to
+    /// them by position for `single` and `list` blocks configured `count`
+    /// times: sets are ordered differently, and a `dynamic` block expands
+    /// into any number of blocks.
+    pub fn block(&self, name: &str, index: usize, count: usize) -> Annotations<'a> {
+        let nested = self.schema.and_then(|schema| schema.block_types.get(name));
+        let positional = nested.is_some_and(|nested| {
+            matches!(nested.nesting_mode.as_str(), "single" | "group" | "list")
+        });
+        Annotations {
+            drift: None,
+            schema: nested.map(|nested| &nested.block),
+            sources: self
+                .sources
+                .filter(|sources| positional && sources.block_count(name) == count)
+                .and_then(|sources| sources.block(name, index)),
+        }
+    }
+
+    /// `computed` and `deprecated`, from the schema.
+    pub fn note(&self, name: &str) -> Option<String> {
+        self.schema?.note(name)
+    }
+
+    pub fn description(&self, name: &str) -> Option<&'a str> {
+        self.schema?.description(name)
+    }
+
+    /// The references an attribute's expression makes, e.g.
+    /// `var.instance_type`.
+    pub fn source(&self, name: &str) -> Option<&'a str> {
+        self.sources?.attribute(name)
+    }
+
+    /// ` <- var.instance_type # deprecated`, to follow an attribute's value
+    /// in the text layouts.
+    pub fn suffix(&self, name: &str) -> String {
+        let mut suffix = String::new();
+        if let Some(source) = self.source(name) {
+            suffix.push_str(&format!(" <- {}", source));
+        }
+        if let Some(note) = self.note(name) {
+            suffix.push_str(&format!(" # {}", note));
+        }
+        suffix
+    }
+}
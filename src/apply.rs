@@ -0,0 +1,307 @@
+use crate::stream::{actions, StreamChange, StreamResource};
+use crate::{action_symbol, format_diagnostics, get_action, Diagnostic, ResourceChange};
+use serde::Deserialize;
+
+/// Where a resource is in an apply.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ApplyStatus {
+    Pending,
+    InProgress,
+    Done,
+    Failed,
+}
+
+/// One resource instance of an apply, as last reported by the stream.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResourceProgress {
+    pub address: String,
+    /// The action as returned by [`get_action`](crate::get_action).
+    pub action: &'static str,
+    pub status: ApplyStatus,
+    pub elapsed_seconds: u64,
+    /// `id=i-0abc` once the object exists.
+    pub id: Option<String>,
+}
+
+impl ResourceProgress {
+    /// `+ aws_instance.web: creating... [10s elapsed]`, or `created after 32s
+    /// [id=i-0abc]` once done.
+    pub fn format(&self) -> String {
+        let (present, past) = match self.action {
+            "create" => ("creating", "created"),
+            "update" => ("modifying", "modified"),
+            "delete" => ("destroying", "destroyed"),
+            "replace" => ("replacing", "replaced"),
+            "read" => ("reading", "read"),
+            _ => ("applying", "applied"),
+        };
+        let elapsed = format_elapsed(self.elapsed_seconds);
+        let status = match self.status {
+            ApplyStatus::Pending => "pending".to_string(),
+            ApplyStatus::InProgress => format!("{}... [{} elapsed]", present, elapsed),
+            ApplyStatus::Done => match &self.id {
+                Some(id) => format!("{} after {} [{}]", past, elapsed, id),
+                None => format!("{} after {}", past, elapsed),
+            },
+            ApplyStatus::Failed => format!("failed after {}", elapsed),
+        };
+        format!(
+            "{} {}: {}",
+            action_symbol(self.action),
+            self.address,
+            status
+        )
+    }
+}
+
+#[derive(Deserialize)]
+struct Message {
+    #[serde(rename = "@message", default)]
+    text: String,
+    #[serde(rename = "type", default)]
+    kind: String,
+    #[serde(default)]
+    change: Option<StreamChange>,
+    #[serde(default)]
+    hook: Option<Hook>,
+    #[serde(default)]
+    diagnostic: Option<Diagnostic>,
+    #[serde(default)]
+    changes: Option<ChangeSummary>,
+}
+
+#[derive(Deserialize)]
+struct Hook {
+    resource: StreamResource,
+    action: String,
+    #[serde(default)]
+    id_key: Option<String>,
+    #[serde(default)]
+    id_value: Option<String>,
+    #[serde(default)]
+    elapsed_seconds: u64,
+}
+
+#[derive(Deserialize)]
+struct ChangeSummary {
+    operation: String,
+}
+
+/// The state of an apply, built up from the `terraform apply -json` stream
+/// one line at a time.
+#[derive(Debug, Clone, Default)]
+pub struct ApplyProgress {
+    /// In the order Terraform planned or started them.
+    pub resources: Vec<ResourceProgress>,
+    pub diagnostics: Vec<Diagnostic>,
+    /// Terraform's closing line, e.g. `Apply complete! Resources: 1 added,
+    /// 0 changed, 0 destroyed.`
+    pub summary: Option<String>,
+}
+
+impl ApplyProgress {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Reads one line of the stream and returns the index of the resource it
+    /// updated, if any. `planned_change` messages add pending resources and
+    /// the `apply_*` hooks move them along.
+    pub fn update(&mut self, line: &str) -> Result<Option<usize>, String> {
+        if line.trim().is_empty() {
+            return Ok(None);
+        }
+        let message: Message = serde_json::from_str(line).map_err(|e| e.to_string())?;
+        let status = match message.kind.as_str() {
+            "planned_change" => {
+                let Some(change) = message.change else {
+                    return Ok(None);
+                };
+                let change = ResourceChange::from(change);
+                let action = get_action(&change.change.actions);
+                if action == "unknown" {
+                    return Ok(None);
+                }
+                let index = self.index(&change.address, action);
+                return Ok(Some(index));
+            }
+            "apply_start" | "apply_progress" => ApplyStatus::InProgress,
+            "apply_complete" => ApplyStatus::Done,
+            "apply_errored" => ApplyStatus::Failed,
+            "diagnostic" => {
+                self.diagnostics.extend(message.diagnostic);
+                return Ok(None);
+            }
+            "change_summary" => {
+                if message
+                    .changes
+                    .is_some_and(|changes| changes.operation != "plan")
+                {
+                    self.summary = Some(message.text);
+                }
+                return Ok(None);
+            }
+            _ => return Ok(None),
+        };
+        let Some(hook) = message.hook else {
+            return Ok(None);
+        };
+        let index = self.index(&hook.resource.addr, get_action(&actions(&hook.action)));
+        let resource = &mut self.resources[index];
+        resource.status = status;
+        resource.elapsed_seconds = hook.elapsed_seconds;
+        if let (Some(key), Some(value)) = (hook.id_key, hook.id_value) {
+            resource.id = Some(format!("{}={}", key, value));
+        }
+        Ok(Some(index))
+    }
+
+    /// The resource at `address`, added as pending if the stream did not
+    /// announce it. The planned action is kept, since hooks report the
+    /// halves of a replacement separately.
+    fn index(&mut self, address: &str, action: &'static str) -> usize {
+        if let Some(index) = self.resources.iter().position(|r| r.address == address) {
+            return index;
+        }
+        self.resources.push(ResourceProgress {
+            address: address.to_string(),
+            action,
+            status: ApplyStatus::Pending,
+            elapsed_seconds: 0,
+            id: None,
+        });
+        self.resources.len() - 1
+    }
+
+    pub fn count(&self, status: ApplyStatus) -> usize {
+        self.resources.iter().filter(|r| r.status == status).count()
+    }
+
+    /// Whether a resource failed or Terraform reported an error.
+    pub fn has_errors(&self) -> bool {
+        self.count(ApplyStatus::Failed) > 0 || self.diagnostics.iter().any(Diagnostic::is_error)
+    }
+
+    /// One line per resource.
+    pub fn format_progress(&self) -> String {
+        self.resources
+            .iter()
+            .map(|resource| format!("{}\n", resource.format()))
+            .collect()
+    }
+
+    /// The final state of every resource, the diagnostics, and a count of
+    /// how the resources ended up.
+    pub fn format_report(&self) -> String {
+        let mut output = self.format_progress();
+        if !self.resources.is_empty() {
+            output.push('\n');
+        }
+        output.push_str(&format_diagnostics(&self.diagnostics));
+        let unfinished = self.count(ApplyStatus::Pending) + self.count(ApplyStatus::InProgress);
+        output.push_str(&format!(
+            "Apply report: {} done, {} failed, {} not finished.\n",
+            self.count(ApplyStatus::Done),
+            self.count(ApplyStatus::Failed),
+            unfinished
+        ));
+        if let Some(summary) = &self.summary {
+            output.push_str(summary);
+            output.push('\n');
+        }
+        output
+    }
+}
+
+/// `45s`, `1m5s` or `1h2m0s`, as Terraform reports elapsed time.
+pub fn format_elapsed(seconds: u64) -> String {
+    let (hours, minutes, seconds) = (seconds / 3600, seconds / 60 % 60, seconds % 60);
+    if hours > 0 {
+        format!("{}h{}m{}s", hours, minutes, seconds)
+    } else if minutes > 0 {
+        format!("{}m{}s", minutes, seconds)
+    } else {
+        format!("{}s", seconds)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RESOURCE: &str = r#""resource":{"addr":"aws_instance.web","module":"","resource":"aws_instance.web","implied_provider":"aws","resource_type":"aws_instance","resource_name":"web","resource_key":null}"#;
+
+    fn message(kind: &str, body: &str) -> String {
+        format!(r#"{{"@message":"","type":"{}",{}}}"#, kind, body)
+    }
+
+    #[test]
+    fn test_apply_progress() {
+        let mut progress = ApplyProgress::new();
+        let planned = message(
+            "planned_change",
+            &format!(r#""change":{{{},"action":"create"}}"#, RESOURCE),
+        );
+        assert_eq!(progress.update(&planned), Ok(Some(0)));
+        assert_eq!(progress.format_progress(), "+ aws_instance.web: pending\n");
+
+        let running = message(
+            "apply_progress",
+            &format!(
+                r#""hook":{{{},"action":"create","elapsed_seconds":70}}"#,
+                RESOURCE
+            ),
+        );
+        progress.update(&running).unwrap();
+        assert_eq!(
+            progress.resources[0].format(),
+            "+ aws_instance.web: creating... [1m10s elapsed]"
+        );
+
+        let complete = message(
+            "apply_complete",
+            &format!(
+                r#""hook":{{{},"action":"create","id_key":"id","id_value":"i-0abc","elapsed_seconds":72}}"#,
+                RESOURCE
+            ),
+        );
+        progress.update(&complete).unwrap();
+        progress
+            .update(r#"{"@message":"Apply complete! Resources: 1 added, 0 changed, 0 destroyed.","type":"change_summary","changes":{"add":1,"change":0,"remove":0,"operation":"apply"}}"#)
+            .unwrap();
+
+        assert!(!progress.has_errors());
+        assert_eq!(
+            progress.format_report(),
+            "+ aws_instance.web: created after 1m12s [id=i-0abc]\n\
+             \nApply report: 1 done, 0 failed, 0 not finished.\n\
+             Apply complete! Resources: 1 added, 0 changed, 0 destroyed.\n"
+        );
+    }
+
+    #[test]
+    fn test_failed_apply() {
+        let mut progress = ApplyProgress::new();
+        let errored = message(
+            "apply_errored",
+            &format!(
+                r#""hook":{{{},"action":"delete","elapsed_seconds":5}}"#,
+                RESOURCE
+            ),
+        );
+        assert_eq!(progress.update(&errored), Ok(Some(0)));
+        assert!(progress.has_errors());
+        assert_eq!(
+            progress.resources[0].format(),
+            "- aws_instance.web: failed after 5s"
+        );
+        assert!(progress.update("{").is_err());
+    }
+
+    #[test]
+    fn test_format_elapsed() {
+        assert_eq!(format_elapsed(45), "45s");
+        assert_eq!(format_elapsed(65), "1m5s");
+        assert_eq!(format_elapsed(3720), "1h2m0s");
+    }
+}
@@ -0,0 +1,190 @@
+use serde::Deserialize;
+use std::fmt;
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckStatus {
+    Pass,
+    Fail,
+    Error,
+    /// Not evaluated yet, usually because it depends on values known only
+    /// after apply.
+    #[default]
+    Unknown,
+}
+
+impl CheckStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CheckStatus::Pass => "pass",
+            CheckStatus::Fail => "fail",
+            CheckStatus::Error => "error",
+            CheckStatus::Unknown => "unknown",
+        }
+    }
+
+    /// `passed`, `failed`, `errored` or `unknown`.
+    pub fn label(&self) -> &'static str {
+        match self {
+            CheckStatus::Pass => "passed",
+            CheckStatus::Fail => "failed",
+            CheckStatus::Error => "errored",
+            CheckStatus::Unknown => "unknown",
+        }
+    }
+
+    pub fn is_failure(&self) -> bool {
+        matches!(self, CheckStatus::Fail | CheckStatus::Error)
+    }
+}
+
+impl fmt::Display for CheckStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// An object with conditions to check: a `check` block, or a resource,
+/// output or variable with preconditions, postconditions or validations.
+#[derive(Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct CheckResult {
+    pub address: CheckAddress,
+    pub status: CheckStatus,
+    #[serde(default)]
+    pub instances: Vec<CheckInstance>,
+}
+
+#[derive(Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct CheckAddress {
+    /// `check`, `resource`, `output_value` or `var`.
+    #[serde(default)]
+    pub kind: String,
+    pub to_display: String,
+}
+
+#[derive(Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct CheckInstance {
+    pub address: CheckAddress,
+    pub status: CheckStatus,
+    #[serde(default)]
+    pub problems: Vec<CheckProblem>,
+}
+
+#[derive(Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct CheckProblem {
+    pub message: String,
+}
+
+impl CheckResult {
+    /// `check.health`, `aws_instance.web (conditions)`, `output.url
+    /// (conditions)` or `var.region (validation)`.
+    pub fn describe(&self) -> String {
+        let address = &self.address.to_display;
+        match self.address.kind.as_str() {
+            "resource" | "output_value" => format!("{} (conditions)", address),
+            "var" => format!("{} (validation)", address),
+            _ => address.clone(),
+        }
+    }
+
+    /// The failure messages, prefixed with the instance address when the
+    /// object has several instances.
+    pub fn messages(&self) -> Vec<String> {
+        self.instances
+            .iter()
+            .flat_map(|instance| {
+                instance.problems.iter().map(move |problem| {
+                    if instance.address.to_display == self.address.to_display {
+                        problem.message.clone()
+                    } else {
+                        format!("{}: {}", instance.address.to_display, problem.message)
+                    }
+                })
+            })
+            .collect()
+    }
+}
+
+/// How many checks have each status.
+pub fn count_checks(checks: &[CheckResult], status: CheckStatus) -> usize {
+    checks.iter().filter(|check| check.status == status).count()
+}
+
+/// `Checks: 2 passed, 1 failed, 1 unknown` with each failed check and its
+/// messages, or nothing when the plan has no checks.
+pub fn format_checks(checks: &[CheckResult]) -> String {
+    if checks.is_empty() {
+        return String::new();
+    }
+    let mut output = format!("Checks: {}\n", format_check_counts(checks));
+    for check in checks.iter().filter(|check| check.status.is_failure()) {
+        output.push_str(&format!(
+            "    ✗ {} {}\n",
+            check.describe(),
+            check.status.label()
+        ));
+        for message in check.messages() {
+            output.push_str(&format!("        {}\n", message));
+        }
+    }
+    output.push('\n');
+    output
+}
+
+/// `2 passed, 1 failed, 1 unknown`, leaving out statuses no check has.
+pub fn format_check_counts(checks: &[CheckResult]) -> String {
+    [
+        CheckStatus::Pass,
+        CheckStatus::Fail,
+        CheckStatus::Error,
+        CheckStatus::Unknown,
+    ]
+    .iter()
+    .map(|status| (count_checks(checks, *status), status.label()))
+    .filter(|(count, _)| *count > 0)
+    .map(|(count, label)| format!("{} {}", count, label))
+    .collect::<Vec<_>>()
+    .join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_format_checks() {
+        let checks: Vec<CheckResult> = serde_json::from_value(json!([
+            {
+                "address": {"kind": "check", "name": "health", "to_display": "check.health"},
+                "status": "fail",
+                "instances": [{
+                    "address": {"to_display": "check.health"},
+                    "status": "fail",
+                    "problems": [{"message": "Health endpoint returned 503."}]
+                }]
+            },
+            {
+                "address": {"kind": "resource", "to_display": "aws_instance.web"},
+                "status": "fail",
+                "instances": [{
+                    "address": {"to_display": "aws_instance.web[1]"},
+                    "status": "fail",
+                    "problems": [{"message": "The AMI must be for x86_64."}]
+                }]
+            },
+            {"address": {"kind": "var", "to_display": "var.region"}, "status": "pass"},
+            {"address": {"kind": "output_value", "to_display": "output.url"}, "status": "unknown"}
+        ]))
+        .unwrap();
+
+        assert_eq!(
+            format_checks(&checks),
+            "Checks: 1 passed, 2 failed, 1 unknown\n\
+             \x20   ✗ check.health failed\n\
+             \x20       Health endpoint returned 503.\n\
+             \x20   ✗ aws_instance.web (conditions) failed\n\
+             \x20       aws_instance.web[1]: The AMI must be for x86_64.\n\n"
+        );
+    }
+}
@@ -0,0 +1,288 @@
+use crate::{parse_duration, FormatOptions, IgnorePath, NoiseFilter, RiskClassifier, RiskRule};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// File name searched for in the working directory and its parents.
+pub const CONFIG_FILE_NAME: &str = ".tfplan.toml";
+
+/// How the plan is written out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OutputFormat {
+    #[default]
+    Text,
+    Html,
+    Interactive,
+    /// For pull request comments.
+    Markdown,
+}
+
+impl OutputFormat {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OutputFormat::Text => "text",
+            OutputFormat::Html => "html",
+            OutputFormat::Interactive => "interactive",
+            OutputFormat::Markdown => "markdown",
+        }
+    }
+}
+
+impl fmt::Display for OutputFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "text" => Ok(OutputFormat::Text),
+            "html" => Ok(OutputFormat::Html),
+            "interactive" => Ok(OutputFormat::Interactive),
+            "markdown" | "md" => Ok(OutputFormat::Markdown),
+            other => Err(format!(
+                "unknown output format '{}' (expected text, html, interactive or markdown)",
+                other
+            )),
+        }
+    }
+}
+
+/// Settings from a `.tfplan.toml` file or the command line. Unset values
+/// fall back to the defaults of [`FormatOptions`].
+///
+/// ```toml
+/// format = "html"
+/// sort = "action"
+/// reduce-noise = true
+/// ignore = ["aws_instance:user_data"]
+/// risk-rules = ["aws_instance:delete=critical"]
+/// ```
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+pub struct Config {
+    /// `text`, `html` or `interactive`.
+    pub format: Option<String>,
+    pub collapsed: Option<bool>,
+    pub sort: Option<String>,
+    pub style: Option<String>,
+    pub stats: Option<bool>,
+    pub generated_config: Option<bool>,
+    pub reduce_noise: Option<bool>,
+    pub show_unchanged: Option<bool>,
+    /// Warn about plans older than this, e.g. `24h` or `7d`.
+    pub max_age: Option<String>,
+    /// Exit with status 1 when a check fails.
+    pub fail_on_checks: Option<bool>,
+    /// Show drift the planned changes do not depend on.
+    pub all_drift: Option<bool>,
+    /// Provider schemas from `terraform providers schema -json`.
+    pub schema: Option<String>,
+    /// `[TYPE:]PATH` attributes whose differences are ignored.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ignore: Vec<String>,
+    /// `TYPE[:ACTIONS][@ATTRIBUTES]=LEVEL` risk overrides.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub risk_rules: Vec<String>,
+}
+
+impl Config {
+    /// Parses and validates a TOML configuration.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let config: Config = toml::from_str(text).map_err(|e| e.message().to_string())?;
+        config.output_format()?;
+        config.format_options()?;
+        Ok(config)
+    }
+
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text = fs::read_to_string(path)
+            .map_err(|e| format!("cannot read {}: {}", path.display(), e))?;
+        Self::parse(&text).map_err(|e| format!("invalid {}: {}", path.display(), e))
+    }
+
+    /// Layers `overrides` on top of this configuration: values it sets win,
+    /// and its ignore paths and risk rules are added, its rules first so they
+    /// take precedence.
+    pub fn merge(self, overrides: Config) -> Config {
+        Config {
+            format: overrides.format.or(self.format),
+            collapsed: overrides.collapsed.or(self.collapsed),
+            sort: overrides.sort.or(self.sort),
+            style: overrides.style.or(self.style),
+            stats: overrides.stats.or(self.stats),
+            generated_config: overrides.generated_config.or(self.generated_config),
+            reduce_noise: overrides.reduce_noise.or(self.reduce_noise),
+            show_unchanged: overrides.show_unchanged.or(self.show_unchanged),
+            max_age: overrides.max_age.or(self.max_age),
+            fail_on_checks: overrides.fail_on_checks.or(self.fail_on_checks),
+            all_drift: overrides.all_drift.or(self.all_drift),
+            schema: overrides.schema.or(self.schema),
+            ignore: self.ignore.into_iter().chain(overrides.ignore).collect(),
+            risk_rules: overrides
+                .risk_rules
+                .into_iter()
+                .chain(self.risk_rules)
+                .collect(),
+        }
+    }
+
+    pub fn output_format(&self) -> Result<OutputFormat, String> {
+        self.format
+            .as_deref()
+            .map_or(Ok(OutputFormat::default()), str::parse)
+    }
+
+    /// Builds the renderer options these settings describe.
+    pub fn format_options(&self) -> Result<FormatOptions, String> {
+        let risk = self
+            .risk_rules
+            .iter()
+            .map(|rule| rule.parse::<RiskRule>())
+            .collect::<Result<Vec<_>, _>>()?
+            .into_iter()
+            .fold(RiskClassifier::new(), RiskClassifier::with_rule);
+
+        let ignore = self
+            .ignore
+            .iter()
+            .map(|path| path.parse::<IgnorePath>())
+            .collect::<Result<Vec<_>, _>>()?;
+        let noise = (self.reduce_noise.unwrap_or(false) || !ignore.is_empty()).then(|| {
+            ignore
+                .into_iter()
+                .fold(NoiseFilter::new(), NoiseFilter::with_ignore)
+        });
+
+        Ok(FormatOptions {
+            collapsed: self.collapsed.unwrap_or(false),
+            risk,
+            sort: self
+                .sort
+                .as_deref()
+                .map_or(Ok(Default::default()), str::parse)?,
+            stats: self.stats.unwrap_or(false),
+            generated_config: self.generated_config.unwrap_or(false),
+            noise,
+            show_unchanged: self.show_unchanged.unwrap_or(false),
+            style: self
+                .style
+                .as_deref()
+                .map_or(Ok(Default::default()), str::parse)?,
+            max_age: self.max_age.as_deref().map(parse_duration).transpose()?,
+            fail_on_checks: self.fail_on_checks.unwrap_or(false),
+            all_drift: self.all_drift.unwrap_or(false),
+            ..Default::default()
+        })
+    }
+
+    /// Every setting with its effective value, defaults included.
+    pub fn resolved(&self) -> Result<Config, String> {
+        let options = self.format_options()?;
+        Ok(Config {
+            format: Some(self.output_format()?.to_string()),
+            collapsed: Some(options.collapsed),
+            sort: Some(options.sort.to_string()),
+            style: Some(options.style.to_string()),
+            stats: Some(options.stats),
+            generated_config: Some(options.generated_config),
+            reduce_noise: Some(options.noise.is_some()),
+            show_unchanged: Some(options.show_unchanged),
+            max_age: self.max_age.clone(),
+            fail_on_checks: Some(options.fail_on_checks),
+            all_drift: Some(options.all_drift),
+            schema: self.schema.clone(),
+            ignore: self.ignore.clone(),
+            risk_rules: self.risk_rules.clone(),
+        })
+    }
+
+    pub fn to_toml(&self) -> String {
+        toml::to_string(self).unwrap_or_default()
+    }
+}
+
+/// Finds the configuration file: the nearest `.tfplan.toml` from `dir`
+/// upwards, then `tfplan/config.toml` under the XDG config directory.
+pub fn find_config_file(dir: &Path, xdg_config_home: Option<&Path>) -> Option<PathBuf> {
+    dir.ancestors()
+        .map(|ancestor| ancestor.join(CONFIG_FILE_NAME))
+        .chain(xdg_config_home.map(|home| home.join("tfplan").join("config.toml")))
+        .find(|path| path.is_file())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{RenderStyle, SortOrder};
+
+    #[test]
+    fn test_parse_and_merge() {
+        let file = Config::parse(
+            r#"
+format = "html"
+sort = "action"
+reduce-noise = true
+max-age = "7d"
+ignore = ["aws_instance:user_data"]
+risk-rules = ["aws_instance=high"]
+"#,
+        )
+        .unwrap();
+        let cli = Config {
+            format: Some("text".to_string()),
+            style: Some("hcl".to_string()),
+            risk_rules: vec!["aws_instance=low".to_string()],
+            ..Default::default()
+        };
+
+        let merged = file.merge(cli);
+        assert_eq!(merged.output_format(), Ok(OutputFormat::Text));
+        assert_eq!(
+            merged.risk_rules,
+            vec!["aws_instance=low", "aws_instance=high"]
+        );
+
+        let options = merged.format_options().unwrap();
+        assert_eq!(options.sort, SortOrder::Action);
+        assert_eq!(options.style, RenderStyle::Hcl);
+        assert!(options.noise.is_some());
+        assert_eq!(options.max_age, Some(7 * 86400));
+        assert!(merged.to_toml().contains("reduce-noise = true\n"));
+    }
+
+    #[test]
+    fn test_invalid_config() {
+        assert!(Config::parse("sort = \"random\"").is_err());
+        assert!(Config::parse("max-age = \"a week\"").is_err());
+        assert!(Config::parse("colour = true").is_err());
+        assert!(Config::parse("risk-rules = [\"aws_instance\"]").is_err());
+    }
+
+    #[test]
+    fn test_find_config_file() {
+        let root = std::env::temp_dir().join(format!("tfplan-config-{}", std::process::id()));
+        let nested = root.join("envs").join("prod");
+        let xdg = root.join("xdg");
+        fs::create_dir_all(&nested).unwrap();
+        fs::create_dir_all(xdg.join("tfplan")).unwrap();
+        fs::write(xdg.join("tfplan").join("config.toml"), "").unwrap();
+
+        assert_eq!(
+            find_config_file(&nested, Some(&xdg)),
+            Some(xdg.join("tfplan").join("config.toml"))
+        );
+        fs::write(root.join(CONFIG_FILE_NAME), "").unwrap();
+        assert_eq!(
+            find_config_file(&nested, Some(&xdg)),
+            Some(root.join(CONFIG_FILE_NAME))
+        );
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,347 @@
+use crate::ResourceChange;
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+/// The `configuration` section of a plan document: the Terraform
+/// configuration the plan was made from.
+#[derive(Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct Configuration {
+    #[serde(default)]
+    pub root_module: ConfigModule,
+}
+
+#[derive(Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct ConfigModule {
+    #[serde(default)]
+    pub variables: BTreeMap<String, VariableDeclaration>,
+    #[serde(default)]
+    pub resources: Vec<ConfigResource>,
+    #[serde(default)]
+    pub module_calls: BTreeMap<String, ModuleCall>,
+}
+
+/// A `variable` block.
+#[derive(Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct VariableDeclaration {
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub sensitive: bool,
+}
+
+/// A `resource` or `data` block.
+#[derive(Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct ConfigResource {
+    /// Relative to the module, e.g. `aws_instance.web`.
+    pub address: String,
+    /// `managed` for resources, `data` for data sources.
+    #[serde(default)]
+    pub mode: String,
+    #[serde(rename = "type", default)]
+    pub resource_type: String,
+    #[serde(default)]
+    pub name: String,
+    /// The arguments and nested blocks of the block.
+    #[serde(default)]
+    pub expressions: BTreeMap<String, ConfigExpression>,
+    #[serde(default)]
+    pub count_expression: Option<Expression>,
+    #[serde(default)]
+    pub for_each_expression: Option<Expression>,
+}
+
+/// A `module` block.
+#[derive(Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct ModuleCall {
+    #[serde(default)]
+    pub source: String,
+    /// The input variables the call sets.
+    #[serde(default)]
+    pub expressions: BTreeMap<String, ConfigExpression>,
+    #[serde(default)]
+    pub module: ConfigModule,
+    #[serde(default)]
+    pub count_expression: Option<Expression>,
+    #[serde(default)]
+    pub for_each_expression: Option<Expression>,
+}
+
+/// An argument, or the arguments of nested blocks: a list of them for
+/// `list` and `set` blocks, and a single one otherwise.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum ConfigExpression {
+    Expression(Expression),
+    Blocks(Vec<BTreeMap<String, ConfigExpression>>),
+    Block(BTreeMap<String, ConfigExpression>),
+}
+
+/// An argument's value: the references it makes, or its value when it is a
+/// constant.
+#[derive(Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct Expression {
+    #[serde(default)]
+    pub references: Vec<String>,
+    #[serde(default)]
+    pub constant_value: Option<Value>,
+}
+
+impl Expression {
+    /// The references, leaving out those another reference refines:
+    /// Terraform lists both `aws_vpc.main.id` and `aws_vpc.main`.
+    pub fn sources(&self) -> Vec<&str> {
+        self.references
+            .iter()
+            .filter(|reference| {
+                !self.references.iter().any(|other| {
+                    other.len() > reference.len()
+                        && other.starts_with(reference.as_str())
+                        && other[reference.len()..].starts_with(['.', '['])
+                })
+            })
+            .map(String::as_str)
+            .collect()
+    }
+}
+
+/// Where the arguments of a resource or nested block get their values, e.g.
+/// `instance_type` from `var.instance_type`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ExpressionSources {
+    attributes: BTreeMap<String, String>,
+    /// The sources of each nested block, by block type in configuration
+    /// order.
+    blocks: BTreeMap<String, Vec<ExpressionSources>>,
+}
+
+impl ExpressionSources {
+    /// Resolves `expressions` in a module reached through `calls`, outermost
+    /// first, so that a module's `var.x` is followed to what its caller sets
+    /// `x` to.
+    fn new(expressions: &BTreeMap<String, ConfigExpression>, calls: &[&ModuleCall]) -> Self {
+        let mut sources = ExpressionSources::default();
+        for (name, expression) in expressions {
+            match expression {
+                ConfigExpression::Expression(expression) => {
+                    let references: Vec<String> = expression
+                        .sources()
+                        .into_iter()
+                        .map(|reference| resolve(reference, calls))
+                        .collect();
+                    if !references.is_empty() {
+                        sources
+                            .attributes
+                            .insert(name.clone(), references.join(", "));
+                    }
+                }
+                ConfigExpression::Blocks(blocks) => {
+                    let blocks = blocks.iter().map(|block| Self::new(block, calls));
+                    sources.blocks.insert(name.clone(), blocks.collect());
+                }
+                ConfigExpression::Block(block) => {
+                    sources
+                        .blocks
+                        .insert(name.clone(), vec![Self::new(block, calls)]);
+                }
+            }
+        }
+        sources
+    }
+
+    /// e.g. `var.instance_type`, or `var.cidr <- var.vpc_cidr` for a module
+    /// variable.
+    pub fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes.get(name).map(String::as_str)
+    }
+
+    /// The sources of the `index`th `name` block.
+    pub fn block(&self, name: &str, index: usize) -> Option<&ExpressionSources> {
+        self.blocks.get(name)?.get(index)
+    }
+
+    /// How many `name` blocks are configured.
+    pub fn block_count(&self, name: &str) -> usize {
+        self.blocks.get(name).map_or(0, Vec::len)
+    }
+}
+
+/// `var.cidr <- var.vpc_cidr` when a module variable is set from the
+/// calling module's references, the reference unchanged otherwise.
+fn resolve(reference: &str, calls: &[&ModuleCall]) -> String {
+    let (Some(call), Some(variable)) = (calls.last(), reference.strip_prefix("var.")) else {
+        return reference.to_string();
+    };
+    let name = variable.split(['.', '[']).next().unwrap_or(variable);
+    let parents = match call.expressions.get(name) {
+        Some(ConfigExpression::Expression(expression)) => expression.sources(),
+        _ => Vec::new(),
+    };
+    if parents.is_empty() {
+        return reference.to_string();
+    }
+    let parents: Vec<String> = parents
+        .into_iter()
+        .map(|parent| resolve(parent, &calls[..calls.len() - 1]))
+        .collect();
+    format!("{} <- {}", reference, parents.join(", "))
+}
+
+impl Configuration {
+    /// The `resource` or `data` block a change is for.
+    pub fn resource(&self, change: &ResourceChange) -> Option<&ConfigResource> {
+        self.find(change).map(|(resource, _)| resource)
+    }
+
+    /// Where the arguments of a change's resource get their values.
+    pub fn sources(&self, change: &ResourceChange) -> Option<ExpressionSources> {
+        let (resource, calls) = self.find(change)?;
+        Some(ExpressionSources::new(&resource.expressions, &calls))
+    }
+
+    fn find(&self, change: &ResourceChange) -> Option<(&ConfigResource, Vec<&ModuleCall>)> {
+        let mut module = &self.root_module;
+        let mut calls = Vec::new();
+        for name in module_path(change.module_address.as_deref().unwrap_or_default()) {
+            let call = module.module_calls.get(&name)?;
+            calls.push(call);
+            module = &call.module;
+        }
+        let mode = if change.mode == "data" {
+            "data"
+        } else {
+            "managed"
+        };
+        let resource = module.resources.iter().find(|resource| {
+            resource.mode == mode
+                && resource.resource_type == change.resource_type
+                && resource.name == change.name
+        })?;
+        Some((resource, calls))
+    }
+}
+
+/// The module call names in a module address, e.g. `["net", "subnets"]` for
+/// `module.net[0].module.subnets["a.b"]`.
+fn module_path(address: &str) -> Vec<String> {
+    let mut path = Vec::new();
+    let mut rest = address;
+    while let Some(after) = rest.strip_prefix("module.") {
+        let end = after.find(['.', '[']).unwrap_or(after.len());
+        path.push(after[..end].to_string());
+        rest = &after[end..];
+        if rest.starts_with('[') {
+            let close = if rest.starts_with("[\"") {
+                rest.find("\"]").map(|index| index + 2)
+            } else {
+                rest.find(']').map(|index| index + 1)
+            };
+            rest = &rest[close.unwrap_or(rest.len())..];
+        }
+        rest = rest.strip_prefix('.').unwrap_or(rest);
+    }
+    path
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn configuration() -> Configuration {
+        serde_json::from_value(json!({
+            "root_module": {
+                "resources": [{
+                    "address": "aws_instance.web",
+                    "mode": "managed",
+                    "type": "aws_instance",
+                    "name": "web",
+                    "expressions": {
+                        "ami": {"references": ["data.aws_ami.ubuntu.id", "data.aws_ami.ubuntu"]},
+                        "instance_type": {"references": ["var.instance_type"]},
+                        "tags": {"constant_value": {"Name": "web"}},
+                        "root_block_device": [{"volume_size": {"references": ["local.disk_size"]}}],
+                        "timeouts": {"create": {"constant_value": "10m"}}
+                    }
+                }],
+                "module_calls": {
+                    "net": {
+                        "source": "./modules/net",
+                        "expressions": {"cidr": {"references": ["var.vpc_cidr"]}},
+                        "module": {
+                            "resources": [{
+                                "address": "aws_vpc.main",
+                                "mode": "managed",
+                                "type": "aws_vpc",
+                                "name": "main",
+                                "expressions": {"cidr_block": {"references": ["var.cidr"]}}
+                            }]
+                        }
+                    }
+                }
+            }
+        }))
+        .unwrap()
+    }
+
+    fn change(
+        address: &str,
+        module: Option<&str>,
+        resource_type: &str,
+        name: &str,
+    ) -> ResourceChange {
+        ResourceChange {
+            address: address.to_string(),
+            mode: "managed".to_string(),
+            resource_type: resource_type.to_string(),
+            name: name.to_string(),
+            module_address: module.map(str::to_string),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_expression_sources() {
+        let configuration = configuration();
+        let web = change("aws_instance.web[0]", None, "aws_instance", "web");
+        let sources = configuration.sources(&web).unwrap();
+
+        assert_eq!(sources.attribute("ami"), Some("data.aws_ami.ubuntu.id"));
+        assert_eq!(
+            sources.attribute("instance_type"),
+            Some("var.instance_type")
+        );
+        assert_eq!(sources.attribute("tags"), None);
+        assert_eq!(
+            sources
+                .block("root_block_device", 0)
+                .and_then(|block| block.attribute("volume_size")),
+            Some("local.disk_size")
+        );
+        assert!(sources.block("timeouts", 0).is_some());
+    }
+
+    #[test]
+    fn test_module_variables_resolve_to_callers() {
+        let configuration = configuration();
+        let vpc = change(
+            "module.net[\"a.b\"].aws_vpc.main",
+            Some("module.net[\"a.b\"]"),
+            "aws_vpc",
+            "main",
+        );
+        let sources = configuration.sources(&vpc).unwrap();
+        assert_eq!(
+            sources.attribute("cidr_block"),
+            Some("var.cidr <- var.vpc_cidr")
+        );
+        assert_eq!(
+            module_path("module.net[0].module.subnets[\"a.b\"]"),
+            ["net", "subnets"]
+        );
+        assert!(configuration
+            .sources(&change("aws_vpc.main", None, "aws_vpc", "main"))
+            .is_none());
+    }
+}
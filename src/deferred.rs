@@ -0,0 +1,105 @@
+use crate::{action_symbol, get_action, ResourceChange};
+use serde::Deserialize;
+
+/// A resource change Terraform could not plan yet. It is planned by a later
+/// plan, once this one is applied.
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct DeferredChange {
+    /// e.g. `instance_count_unknown` or `provider_config_unknown`.
+    pub reason: String,
+    pub resource_change: ResourceChange,
+}
+
+impl DeferredChange {
+    /// A short description of why the change is deferred.
+    pub fn reason_text(&self) -> &str {
+        match self.reason.as_str() {
+            "instance_count_unknown" => "count or for_each unknown",
+            "resource_config_unknown" => "configuration unknown",
+            "provider_config_unknown" => "provider configuration unknown",
+            "absent_prereq" => "prerequisite not created yet",
+            "deferred_prereq" => "prerequisite deferred",
+            other => other,
+        }
+    }
+
+    /// `+ aws_instance.web (count or for_each unknown)`
+    pub fn format(&self) -> String {
+        let change = &self.resource_change;
+        format!(
+            "{} {} ({})",
+            action_symbol(get_action(&change.change.actions)),
+            change.display_address(),
+            self.reason_text()
+        )
+    }
+}
+
+/// `3 (2 count or for_each unknown, 1 configuration unknown)`: the number of
+/// deferred changes and how many were deferred for each reason.
+pub fn format_deferred_counts(deferred: &[DeferredChange]) -> String {
+    let mut reasons: Vec<(&str, usize)> = Vec::new();
+    for change in deferred {
+        match reasons
+            .iter_mut()
+            .find(|(reason, _)| *reason == change.reason_text())
+        {
+            Some((_, count)) => *count += 1,
+            None => reasons.push((change.reason_text(), 1)),
+        }
+    }
+    let reasons: Vec<String> = reasons
+        .iter()
+        .map(|(reason, count)| format!("{} {}", count, reason))
+        .collect();
+    format!("{} ({})", deferred.len(), reasons.join(", "))
+}
+
+pub(crate) fn format_deferred(deferred: &[DeferredChange]) -> String {
+    if deferred.is_empty() {
+        return String::new();
+    }
+    let mut output = format!(
+        "Deferred changes: {}, to be planned after this plan is applied:\n",
+        format_deferred_counts(deferred)
+    );
+    for change in deferred {
+        output.push_str(&format!("    {}\n", change.format()));
+    }
+    output.push('\n');
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_format_deferred() {
+        let deferred: Vec<DeferredChange> = serde_json::from_value(json!([
+            {
+                "reason": "instance_count_unknown",
+                "resource_change": {"address": "aws_instance.web[*]", "change": {"actions": ["create"]}}
+            },
+            {
+                "reason": "provider_config_unknown",
+                "resource_change": {"address": "kubernetes_namespace.app", "change": {"actions": ["create"]}}
+            },
+            {
+                "reason": "instance_count_unknown",
+                "resource_change": {"address": "aws_eip.web[*]", "change": {"actions": ["create"]}}
+            }
+        ]))
+        .unwrap();
+
+        assert_eq!(
+            format_deferred(&deferred),
+            "Deferred changes: 3 (2 count or for_each unknown, 1 provider configuration unknown), \
+             to be planned after this plan is applied:\n\
+             \x20   + aws_instance.web[*] (count or for_each unknown)\n\
+             \x20   + kubernetes_namespace.app (provider configuration unknown)\n\
+             \x20   + aws_eip.web[*] (count or for_each unknown)\n\n"
+        );
+    }
+}
@@ -0,0 +1,320 @@
+use serde::Deserialize;
+use std::fmt;
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    #[default]
+    Error,
+    Warning,
+}
+
+impl Severity {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        }
+    }
+
+    /// `Error` or `Warning`, as Terraform prefixes the summary.
+    pub fn label(&self) -> &'static str {
+        match self {
+            Severity::Error => "Error",
+            Severity::Warning => "Warning",
+        }
+    }
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A warning or error Terraform reported, as found in `diagnostic` messages
+/// of the `-json` UI stream.
+#[derive(Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub summary: String,
+    #[serde(default)]
+    pub detail: String,
+    /// The resource instance the diagnostic is about, if any.
+    #[serde(default)]
+    pub address: Option<String>,
+    #[serde(default)]
+    pub range: Option<SourceRange>,
+    #[serde(default)]
+    pub snippet: Option<Snippet>,
+}
+
+#[derive(Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct SourceRange {
+    pub filename: String,
+    pub start: SourcePos,
+    pub end: SourcePos,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+pub struct SourcePos {
+    pub line: usize,
+    pub column: usize,
+    #[serde(default)]
+    pub byte: usize,
+}
+
+/// The configuration source around a diagnostic's range.
+#[derive(Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct Snippet {
+    /// The enclosing block, e.g. `resource "aws_instance" "web"`.
+    #[serde(default)]
+    pub context: Option<String>,
+    pub code: String,
+    pub start_line: usize,
+    /// Byte offsets of the problematic expression within `code`.
+    pub highlight_start_offset: usize,
+    pub highlight_end_offset: usize,
+    /// Values of the references in the expression, e.g. `var.ami is null`.
+    #[serde(default)]
+    pub values: Vec<ExpressionValue>,
+}
+
+#[derive(Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct ExpressionValue {
+    pub traversal: String,
+    pub statement: String,
+}
+
+/// One line of a snippet split around the highlighted expression.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SnippetLine<'a> {
+    pub number: usize,
+    pub before: &'a str,
+    pub highlight: &'a str,
+    pub after: &'a str,
+}
+
+impl Snippet {
+    /// The code line by line, with the part of each line that falls inside
+    /// the highlight offsets separated out.
+    pub fn lines(&self) -> Vec<SnippetLine<'_>> {
+        let mut start = 0;
+        let mut lines = Vec::new();
+        for (index, line) in self.code.split('\n').enumerate() {
+            let clamp = |offset: usize| offset.saturating_sub(start).min(line.len());
+            let (from, to) = (
+                clamp(self.highlight_start_offset),
+                clamp(self.highlight_end_offset),
+            );
+            let (from, to) =
+                if from <= to && line.is_char_boundary(from) && line.is_char_boundary(to) {
+                    (from, to)
+                } else {
+                    (line.len(), line.len())
+                };
+            lines.push(SnippetLine {
+                number: self.start_line + index,
+                before: &line[..from],
+                highlight: &line[from..to],
+                after: &line[to..],
+            });
+            start += line.len() + 1;
+        }
+        lines
+    }
+}
+
+impl Diagnostic {
+    pub fn is_error(&self) -> bool {
+        self.severity == Severity::Error
+    }
+
+    /// `on main.tf line 12, in resource "aws_instance" "web":`
+    pub fn location(&self) -> Option<String> {
+        let range = self.range.as_ref()?;
+        let context = self
+            .snippet
+            .as_ref()
+            .and_then(|snippet| snippet.context.as_deref())
+            .map(|context| format!(", in {}", context))
+            .unwrap_or_default();
+        Some(format!(
+            "on {} line {}{}:",
+            range.filename, range.start.line, context
+        ))
+    }
+
+    /// Terraform's layout: summary, address and location, the snippet with
+    /// the highlighted expression underlined, then the detail.
+    pub fn format_text(&self) -> String {
+        let mut output = format!("{}: {}\n\n", self.severity.label(), self.summary);
+        if let Some(address) = &self.address {
+            output.push_str(&format!("  with {},\n", address));
+        }
+        if let Some(location) = self.location() {
+            output.push_str(&format!("  {}\n", location));
+        }
+        if let Some(snippet) = &self.snippet {
+            output.push_str(&format_snippet(snippet));
+        }
+        if self.address.is_some() || self.range.is_some() {
+            output.push('\n');
+        }
+        if !self.detail.is_empty() {
+            output.push_str(&self.detail);
+            output.push_str("\n\n");
+        }
+        output
+    }
+
+    pub fn format_markdown(&self) -> String {
+        let mut output = format!("**{}:** {}\n\n", self.severity.label(), self.summary);
+        let location: Vec<String> = self
+            .address
+            .iter()
+            .map(|address| format!("`{}`", address))
+            .chain(
+                self.range
+                    .iter()
+                    .map(|range| format!("`{}` line {}", range.filename, range.start.line)),
+            )
+            .collect();
+        if !location.is_empty() {
+            output.push_str(&location.join(" in "));
+            output.push_str("\n\n");
+        }
+        if let Some(snippet) = &self.snippet {
+            output.push_str("```\n");
+            output.push_str(&format_snippet(snippet));
+            output.push_str("```\n\n");
+        }
+        if !self.detail.is_empty() {
+            output.push_str(&self.detail);
+            output.push_str("\n\n");
+        }
+        output
+    }
+}
+
+/// `  12:   ami = var.amii` lines with `^^^^^^^^` under the highlighted
+/// expression, followed by the values of its references.
+fn format_snippet(snippet: &Snippet) -> String {
+    let lines = snippet.lines();
+    let width = lines.last().map_or(0, |line| line.number.to_string().len());
+    let mut output = String::new();
+    for line in &lines {
+        output.push_str(&format!(
+            "  {:>width$}: {}{}{}\n",
+            line.number,
+            line.before,
+            line.highlight,
+            line.after,
+            width = width
+        ));
+        if !line.highlight.is_empty() {
+            output.push_str(&format!(
+                "  {:width$}  {}{}\n",
+                "",
+                " ".repeat(line.before.chars().count()),
+                "^".repeat(line.highlight.chars().count()),
+                width = width
+            ));
+        }
+    }
+    for value in &snippet.values {
+        output.push_str(&format!("    │ {} {}\n", value.traversal, value.statement));
+    }
+    output
+}
+
+/// Every diagnostic in Terraform's layout, errors first.
+pub fn format_diagnostics(diagnostics: &[Diagnostic]) -> String {
+    ordered(diagnostics).map(Diagnostic::format_text).collect()
+}
+
+/// Every diagnostic as Markdown, errors first, for pull request comments.
+pub fn format_diagnostics_markdown(diagnostics: &[Diagnostic]) -> String {
+    ordered(diagnostics)
+        .map(Diagnostic::format_markdown)
+        .collect()
+}
+
+fn ordered(diagnostics: &[Diagnostic]) -> impl Iterator<Item = &Diagnostic> {
+    let errors = diagnostics.iter().filter(|d| d.is_error());
+    errors.chain(diagnostics.iter().filter(|d| !d.is_error()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn invalid_reference() -> Diagnostic {
+        serde_json::from_value(json!({
+            "severity": "error",
+            "summary": "Reference to undeclared input variable",
+            "detail": "An input variable with the name \"amii\" has not been declared.",
+            "address": "aws_instance.web",
+            "range": {
+                "filename": "main.tf",
+                "start": {"line": 12, "column": 9, "byte": 210},
+                "end": {"line": 12, "column": 17, "byte": 218}
+            },
+            "snippet": {
+                "context": "resource \"aws_instance\" \"web\"",
+                "code": "  ami = var.amii",
+                "start_line": 12,
+                "highlight_start_offset": 8,
+                "highlight_end_offset": 16,
+                "values": [{"traversal": "var.amii", "statement": "is null"}]
+            }
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_format_text() {
+        assert_eq!(
+            invalid_reference().format_text(),
+            "Error: Reference to undeclared input variable\n\
+             \n  with aws_instance.web,\
+             \n  on main.tf line 12, in resource \"aws_instance\" \"web\":\
+             \n  12:   ami = var.amii\
+             \n              ^^^^^^^^\
+             \n    │ var.amii is null\
+             \n\
+             \nAn input variable with the name \"amii\" has not been declared.\n\n"
+        );
+    }
+
+    #[test]
+    fn test_snippet_lines_split_multiline_highlight() {
+        let snippet = Snippet {
+            code: "tags = {\n  Name = 1\n}".to_string(),
+            start_line: 3,
+            highlight_start_offset: 11,
+            highlight_end_offset: 19,
+            ..Default::default()
+        };
+        let lines = snippet.lines();
+        assert_eq!(lines[0].highlight, "");
+        assert_eq!((lines[1].before, lines[1].highlight), ("  ", "Name = 1"));
+        assert_eq!(lines[2].number, 5);
+    }
+
+    #[test]
+    fn test_errors_first_in_markdown() {
+        let warning = Diagnostic {
+            severity: Severity::Warning,
+            summary: "Deprecated attribute".to_string(),
+            ..Default::default()
+        };
+        let output = format_diagnostics_markdown(&[warning, invalid_reference()]);
+        assert!(output.starts_with(
+            "**Error:** Reference to undeclared input variable\n\n\
+             `aws_instance.web` in `main.tf` line 12\n\n```\n  12:   ami = var.amii\n"
+        ));
+        assert!(output.ends_with("**Warning:** Deprecated attribute\n\n"));
+    }
+}
@@ -0,0 +1,188 @@
+use serde_json::Value;
+
+/// Number of unchanged lines shown around each change in a multi-line diff.
+pub const DIFF_CONTEXT_LINES: usize = 3;
+
+/// Above this many line comparisons the diff falls back to replacing every
+/// line, to keep huge attributes from stalling the formatter.
+const MAX_DIFF_CELLS: usize = 4_000_000;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DiffLine {
+    Context(String),
+    Removed(String),
+    Added(String),
+    /// A run of unchanged lines collapsed away.
+    Skipped(usize),
+}
+
+/// Whether a change between two values should be shown as a line diff:
+/// both are strings and at least one spans several lines.
+pub fn is_multiline_change(before: &Value, after: &Value) -> bool {
+    match (before, after) {
+        (Value::String(b), Value::String(a)) => b.contains('\n') || a.contains('\n'),
+        _ => false,
+    }
+}
+
+/// Line-oriented diff of two strings in unified-diff style, keeping `context`
+/// unchanged lines around each change and collapsing the rest.
+pub fn diff_lines(before: &str, after: &str, context: usize) -> Vec<DiffLine> {
+    let old: Vec<&str> = before.lines().collect();
+    let new: Vec<&str> = after.lines().collect();
+    let ops = diff_ops(&old, &new);
+
+    let changed: Vec<bool> = ops
+        .iter()
+        .map(|op| !matches!(op, DiffLine::Context(_)))
+        .collect();
+    let visible: Vec<bool> = (0..ops.len())
+        .map(|i| {
+            let start = i.saturating_sub(context);
+            let end = (i + context + 1).min(ops.len());
+            changed[start..end].iter().any(|c| *c)
+        })
+        .collect();
+
+    let mut lines = Vec::new();
+    let mut skipped = 0;
+    for (op, show) in ops.into_iter().zip(visible) {
+        if show {
+            if skipped > 0 {
+                lines.push(DiffLine::Skipped(skipped));
+                skipped = 0;
+            }
+            lines.push(op);
+        } else {
+            skipped += 1;
+        }
+    }
+    if skipped > 0 {
+        lines.push(DiffLine::Skipped(skipped));
+    }
+    lines
+}
+
+/// Longest-common-subsequence diff producing one entry per line.
+fn diff_ops(old: &[&str], new: &[&str]) -> Vec<DiffLine> {
+    if old.len().saturating_mul(new.len()) > MAX_DIFF_CELLS {
+        return old
+            .iter()
+            .map(|l| DiffLine::Removed(l.to_string()))
+            .chain(new.iter().map(|l| DiffLine::Added(l.to_string())))
+            .collect();
+    }
+
+    // lcs[i][j] is the LCS length of old[i..] and new[j..].
+    let mut lcs = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i][j] = if old[i] == new[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let (mut i, mut j) = (0, 0);
+    let mut ops = Vec::new();
+    while i < old.len() && j < new.len() {
+        if old[i] == new[j] {
+            ops.push(DiffLine::Context(old[i].to_string()));
+            i += 1;
+            j += 1;
+        } else if lcs[i + 1][j] >= lcs[i][j + 1] {
+            ops.push(DiffLine::Removed(old[i].to_string()));
+            i += 1;
+        } else {
+            ops.push(DiffLine::Added(new[j].to_string()));
+            j += 1;
+        }
+    }
+    ops.extend(old[i..].iter().map(|l| DiffLine::Removed(l.to_string())));
+    ops.extend(new[j..].iter().map(|l| DiffLine::Added(l.to_string())));
+    ops
+}
+
+/// Plain-text rendering of a line diff, each line prefixed with `indent`.
+pub fn format_line_diff(lines: &[DiffLine], indent: &str) -> String {
+    let mut output = String::new();
+    for line in lines {
+        let rendered = match line {
+            DiffLine::Context(text) => format!("  {}", text),
+            DiffLine::Removed(text) => format!("- {}", text),
+            DiffLine::Added(text) => format!("+ {}", text),
+            DiffLine::Skipped(count) => format_skipped(*count),
+        };
+        output.push_str(indent);
+        output.push_str(rendered.trim_end());
+        output.push('\n');
+    }
+    output
+}
+
+/// `@@ 1 unchanged line @@` / `@@ N unchanged lines @@`.
+pub fn format_skipped(count: usize) -> String {
+    format!(
+        "@@ {} unchanged line{} @@",
+        count,
+        if count == 1 { "" } else { "s" }
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_is_multiline_change() {
+        assert!(is_multiline_change(&json!("a\nb"), &json!("a")));
+        assert!(!is_multiline_change(&json!("a"), &json!("b")));
+        assert!(!is_multiline_change(&json!(1), &json!("a\nb")));
+    }
+
+    #[test]
+    fn test_diff_lines_with_context() {
+        let before = "1\n2\n3\n4\n5\n6\n7\n8\n9\n10";
+        let after = "1\n2\n3\n4\n5\nsix\n7\n8\n9\n10";
+
+        assert_eq!(
+            diff_lines(before, after, 2),
+            vec![
+                DiffLine::Skipped(3),
+                DiffLine::Context("4".to_string()),
+                DiffLine::Context("5".to_string()),
+                DiffLine::Removed("6".to_string()),
+                DiffLine::Added("six".to_string()),
+                DiffLine::Context("7".to_string()),
+                DiffLine::Context("8".to_string()),
+                DiffLine::Skipped(2),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_diff_lines_insertions() {
+        assert_eq!(
+            diff_lines("a\nc", "a\nb\nc\nd", 3),
+            vec![
+                DiffLine::Context("a".to_string()),
+                DiffLine::Added("b".to_string()),
+                DiffLine::Context("c".to_string()),
+                DiffLine::Added("d".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_format_line_diff() {
+        let lines = diff_lines("#!/bin/bash\necho old\n", "#!/bin/bash\necho new\n", 3);
+        assert_eq!(
+            format_line_diff(&lines, "    "),
+            "      #!/bin/bash\n    - echo old\n    + echo new\n"
+        );
+        assert_eq!(format_skipped(1), "@@ 1 unchanged line @@");
+    }
+}
@@ -0,0 +1,180 @@
+use crate::{changed_attributes, get_action, ResourceChange, TerraformPlan};
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::BTreeSet;
+
+/// An attribute of a drifted resource that contributes to the planned
+/// changes, as listed in `relevant_attributes`.
+#[derive(Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct RelevantAttribute {
+    pub resource: String,
+    /// Path into the resource, e.g. `["tags", "Name"]` or
+    /// `["ingress", 0, "cidr_blocks"]`; empty for the whole resource.
+    #[serde(default)]
+    pub attribute: Vec<Value>,
+}
+
+impl TerraformPlan {
+    /// Whether the plan says which drift matters. Plans since format 1.1
+    /// (Terraform 1.2) list `relevant_attributes`, and leave it out when no
+    /// drift is relevant; older plans and the UI stream do not say.
+    pub fn tracks_relevant_drift(&self) -> bool {
+        if !self.relevant_attributes.is_empty() {
+            return true;
+        }
+        let Some(version) = self.metadata.format_version.as_deref() else {
+            return false;
+        };
+        let mut parts = version
+            .split('.')
+            .map(|part| part.parse::<u32>().unwrap_or(0));
+        (parts.next().unwrap_or(0), parts.next().unwrap_or(0)) >= (1, 1)
+    }
+
+    /// The drift the planned changes depend on, each object narrowed to its
+    /// relevant attributes. Every drifted object is relevant when the plan
+    /// does not say.
+    pub fn relevant_drift(&self) -> Vec<ResourceChange> {
+        if !self.tracks_relevant_drift() {
+            return self.resource_drift.clone();
+        }
+        self.resource_drift
+            .iter()
+            .filter_map(|drift| {
+                let paths: Vec<&[Value]> = self
+                    .relevant_attributes
+                    .iter()
+                    .filter(|relevant| relevant.resource == drift.address)
+                    .map(|relevant| relevant.attribute.as_slice())
+                    .collect();
+                if paths.is_empty() {
+                    return None;
+                }
+                if paths.iter().any(|path| path.is_empty()) {
+                    return Some(drift.clone());
+                }
+                let names: BTreeSet<&str> =
+                    paths.iter().filter_map(|path| path[0].as_str()).collect();
+                Some(narrow(drift, &names))
+            })
+            .collect()
+    }
+
+    /// `resource_drift` as it should be shown: only the relevant drift,
+    /// unless `all` is set.
+    pub fn drift(&self, all: bool) -> Vec<ResourceChange> {
+        if all {
+            self.resource_drift.clone()
+        } else {
+            self.relevant_drift()
+        }
+    }
+}
+
+impl ResourceChange {
+    /// For an object in `resource_drift`, how it drifted, e.g. `ami, tags
+    /// changed outside of Terraform`, to annotate its planned change.
+    pub fn drift_note(&self) -> String {
+        if get_action(&self.change.actions) == "delete" {
+            return "deleted outside of Terraform".to_string();
+        }
+        let attributes = changed_attributes(&self.change);
+        if attributes.is_empty() {
+            "changed outside of Terraform".to_string()
+        } else {
+            format!("{} changed outside of Terraform", attributes.join(", "))
+        }
+    }
+}
+
+/// The drift of the object at `address`, if any.
+pub fn drift_for<'a>(drift: &'a [ResourceChange], address: &str) -> Option<&'a ResourceChange> {
+    drift.iter().find(|change| change.address == address)
+}
+
+/// Keeps only the `names` attributes of a drifted object, unless none of its
+/// changes are left, which happens when a path reaches into an attribute the
+/// drift does not show.
+fn narrow(drift: &ResourceChange, names: &BTreeSet<&str>) -> ResourceChange {
+    let mut narrowed = drift.clone();
+    for value in [&mut narrowed.change.before, &mut narrowed.change.after]
+        .into_iter()
+        .flatten()
+    {
+        if let Value::Object(object) = value {
+            object.retain(|key, _| names.contains(key.as_str()));
+        }
+    }
+    if changed_attributes(&narrowed.change).is_empty() {
+        drift.clone()
+    } else {
+        narrowed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn plan() -> TerraformPlan {
+        serde_json::from_value(json!({
+            "format_version": "1.2",
+            "resource_changes": [],
+            "resource_drift": [
+                {
+                    "address": "aws_instance.web",
+                    "change": {
+                        "actions": ["update"],
+                        "before": {"ami": "a", "tags": {"Owner": "ops"}, "cpu": 1},
+                        "after": {"ami": "b", "tags": {"Owner": "dev"}, "cpu": 2}
+                    }
+                },
+                {
+                    "address": "aws_s3_bucket.logs",
+                    "change": {"actions": ["delete"], "before": {"bucket": "logs"}, "after": null}
+                },
+                {
+                    "address": "aws_iam_role.app",
+                    "change": {"actions": ["update"], "before": {"name": "a"}, "after": {"name": "b"}}
+                }
+            ],
+            "relevant_attributes": [
+                {"resource": "aws_instance.web", "attribute": ["ami"]},
+                {"resource": "aws_instance.web", "attribute": ["tags", "Owner"]},
+                {"resource": "aws_s3_bucket.logs", "attribute": []}
+            ]
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_relevant_drift() {
+        let plan = plan();
+        let drift = plan.relevant_drift();
+        let lines: Vec<String> = drift.iter().map(ResourceChange::format_drift).collect();
+        assert_eq!(
+            lines,
+            [
+                "~ aws_instance.web has changed (ami, tags)",
+                "- aws_s3_bucket.logs has been deleted"
+            ]
+        );
+        assert_eq!(plan.drift(true).len(), 3);
+        assert_eq!(
+            drift_for(&drift, "aws_instance.web").map(ResourceChange::drift_note),
+            Some("ami, tags changed outside of Terraform".to_string())
+        );
+        assert!(drift_for(&drift, "aws_iam_role.app").is_none());
+    }
+
+    #[test]
+    fn test_all_drift_relevant_without_relevant_attributes() {
+        let mut plan = plan();
+        plan.relevant_attributes.clear();
+        assert!(plan.relevant_drift().is_empty());
+
+        plan.metadata.format_version = Some("1.0".to_string());
+        assert_eq!(plan.relevant_drift().len(), 3);
+    }
+}
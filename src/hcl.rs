@@ -0,0 +1,859 @@
+use crate::unchanged::{block_list, is_blank};
+use crate::Annotations;
+use crate::{
+    append_to_line, diff_lines, format_value, get_action, has_unknown, DiffLine, NestedBlock,
+    ResourceChange, RiskLevel,
+};
+use crate::{DIFF_CONTEXT_LINES, SENSITIVE_PLACEHOLDER};
+use serde_json::{Map, Value};
+use std::fmt;
+use std::str::FromStr;
+
+/// Layout used for the attributes of each resource change.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RenderStyle {
+    /// One `key: old => new` line per changed attribute.
+    #[default]
+    Compact,
+    /// Terraform's own HCL-like layout with a `+`/`-`/`~` marker per line.
+    Hcl,
+}
+
+impl RenderStyle {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RenderStyle::Compact => "compact",
+            RenderStyle::Hcl => "hcl",
+        }
+    }
+}
+
+impl fmt::Display for RenderStyle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for RenderStyle {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "compact" => Ok(RenderStyle::Compact),
+            "hcl" | "terraform" => Ok(RenderStyle::Hcl),
+            other => Err(format!(
+                "unknown render style '{}' (expected compact or hcl)",
+                other
+            )),
+        }
+    }
+}
+
+/// Terraform's wording for an action in `# addr will be ...` comments.
+fn action_text(action: &str) -> &str {
+    match action {
+        "create" => "created",
+        "update" => "updated in-place",
+        "delete" => "destroyed",
+        "replace" => "replaced",
+        "read" => "read during apply",
+        other => other,
+    }
+}
+
+/// A resource change as Terraform prints it: a `# addr will be ...` comment
+/// followed, unless `collapsed`, by the `resource "type" "name" { ... }` block.
+pub(crate) fn format_hcl_change(
+    change: &ResourceChange,
+    collapsed: bool,
+    show_unchanged: bool,
+    risk: RiskLevel,
+    annotations: Annotations,
+) -> String {
+    let action = get_action(&change.change.actions);
+    let mut output = format!(
+        "  # {} {}",
+        change.display_address(),
+        change.describe(action_text(action))
+    );
+    if risk.is_high() {
+        output.push_str(&format!(" [{}]", risk.as_str().to_uppercase()));
+    }
+    output.push('\n');
+
+    if !collapsed {
+        output.push_str(&format_hcl_block(change, show_unchanged, annotations));
+    }
+    output.push('\n');
+    output
+}
+
+/// The `resource "type" "name" { ... }` block of a change, with unchanged
+/// attributes and blocks counted unless `show_unchanged` is set. With the
+/// resource's schema, nested blocks are told from attributes by the schema
+/// rather than by their shape, and computed and deprecated attributes are
+/// noted; with its configuration, changed attributes are followed by the
+/// references that set them.
+pub fn format_hcl_block(
+    change: &ResourceChange,
+    show_unchanged: bool,
+    annotations: Annotations,
+) -> String {
+    let action = get_action(&change.change.actions);
+    let prefix = match action {
+        "create" => "  +",
+        "update" => "  ~",
+        "delete" => "  -",
+        "replace" => "-/+",
+        "read" => " <=",
+        _ => "   ",
+    };
+    let keyword = if change.mode == "data" {
+        "data"
+    } else {
+        "resource"
+    };
+
+    let object = |value: &Option<Value>| {
+        value
+            .as_ref()
+            .and_then(Value::as_object)
+            .cloned()
+            .unwrap_or_default()
+    };
+    let before = object(&change.change.before);
+    let after = object(&change.change.after);
+    let unknown = &change.change.after_unknown;
+
+    let mut output = format!(
+        "{} {} \"{}\" \"{}\" {{\n",
+        prefix,
+        keyword,
+        change.resource_type,
+        resource_name(change)
+    );
+    diff_object(
+        &mut output,
+        &before,
+        &after,
+        unknown,
+        6,
+        show_unchanged,
+        annotations,
+    );
+    output.push_str("    }\n");
+    output
+}
+
+/// The resource name, falling back to the last address segment for plans
+/// without a `name` field.
+fn resource_name(change: &ResourceChange) -> &str {
+    if !change.name.is_empty() {
+        return &change.name;
+    }
+    let address = change
+        .address
+        .split_once('[')
+        .map_or(change.address.as_str(), |(address, _)| address);
+    address.rsplit('.').next().unwrap_or(address)
+}
+
+fn push_line(output: &mut String, indent: usize, marker: char, text: &str) {
+    output.push_str(&format!("{}{} {}\n", " ".repeat(indent), marker, text));
+}
+
+fn push_closing(output: &mut String, indent: usize, text: &str) {
+    output.push_str(&format!("{}{}\n", " ".repeat(indent + 2), text));
+}
+
+fn push_hidden(output: &mut String, indent: usize, count: usize, noun: &str) {
+    if count > 0 {
+        push_closing(
+            output,
+            indent,
+            &format!(
+                "# ({} unchanged {}{} hidden)",
+                count,
+                noun,
+                if count == 1 { "" } else { "s" }
+            ),
+        );
+    }
+}
+
+/// Writes the attributes, then the nested blocks, of an object whose lines
+/// start at `indent`. Attributes marked in `unknown`, the object's part of
+/// `after_unknown`, are shown as known after apply.
+fn diff_object(
+    output: &mut String,
+    before: &Map<String, Value>,
+    after: &Map<String, Value>,
+    unknown: &Value,
+    indent: usize,
+    show_unchanged: bool,
+    annotations: Annotations,
+) {
+    let present = |map: &Map<String, Value>, key: &str| -> Option<Value> {
+        map.get(key).filter(|value| !is_blank(value)).cloned()
+    };
+    let is_unknown = |key: &str| unknown.get(key) == Some(&Value::Bool(true));
+    let unknown_keys = unknown.as_object().into_iter().flat_map(Map::keys);
+    let mut keys: Vec<&String> = before
+        .keys()
+        .chain(after.keys())
+        .chain(unknown_keys.filter(|key| is_unknown(key)))
+        .collect();
+    keys.sort();
+    keys.dedup();
+
+    let mut attributes = Vec::new();
+    let mut blocks = Vec::new();
+    for key in keys {
+        let (b, a) = (present(before, key), present(after, key));
+        if is_unknown(key) {
+            attributes.push((key, b, Some(Value::Null)));
+            continue;
+        }
+        if b.is_none() && a.is_none() {
+            continue;
+        }
+        let is_block = match annotations.schema {
+            Some(schema) if schema.attributes.contains_key(key.as_str()) => false,
+            Some(schema) if schema.block_types.contains_key(key.as_str()) => true,
+            _ => [&b, &a]
+                .into_iter()
+                .flatten()
+                .any(|value| block_list(value).is_some()),
+        };
+        if is_block {
+            blocks.push((key, b, a));
+        } else if b != a || show_unchanged {
+            attributes.push((key, b, a));
+        } else {
+            attributes.push((key, None, None));
+        }
+    }
+
+    let width = attributes
+        .iter()
+        .filter(|(_, b, a)| b.is_some() || a.is_some())
+        .map(|(key, _, _)| key.len())
+        .max()
+        .unwrap_or(0);
+    let mut hidden_attributes = 0;
+    for (key, b, a) in &attributes {
+        if b.is_none() && a.is_none() {
+            hidden_attributes += 1;
+            continue;
+        }
+        let name = format!("{:width$}", key, width = width);
+        let start = output.len();
+        if is_unknown(key) {
+            let line = match b {
+                Some(b) => format!(
+                    "{} = {} -> (known after apply)",
+                    name,
+                    render_value(b, '-', indent)
+                ),
+                None => format!("{} = (known after apply)", name),
+            };
+            push_line(output, indent, if b.is_some() { '~' } else { '+' }, &line);
+            append_to_line(output, start, &annotations.suffix(key));
+        } else if diff_value(
+            output,
+            indent,
+            &name,
+            b.as_ref(),
+            a.as_ref(),
+            show_unchanged,
+        ) {
+            hidden_attributes += 1;
+        } else {
+            append_to_line(output, start, &annotations.suffix(key));
+        }
+    }
+    push_hidden(output, indent, hidden_attributes, "attribute");
+
+    let mut hidden_blocks = 0;
+    for (key, b, a) in &blocks {
+        let nested = annotations
+            .schema
+            .and_then(|schema| schema.block_types.get(key.as_str()));
+        let note = annotations
+            .note(key)
+            .map(|note| format!(" # {}", note))
+            .unwrap_or_default();
+        let pairs = block_pairs(nested, b.as_ref(), a.as_ref());
+        let count = pairs
+            .iter()
+            .filter(|(_, _, a_item)| a_item.is_some())
+            .count();
+        for (index, (label, b_item, a_item)) in pairs.into_iter().enumerate() {
+            let unknown = match label {
+                Some(label) => unknown.get(key.as_str()).and_then(|items| items.get(label)),
+                None => unknown.get(key.as_str()).and_then(|items| items.get(index)),
+            };
+            let unknown = unknown.unwrap_or(&Value::Null);
+            if b_item == a_item && !show_unchanged && !has_unknown(unknown) {
+                hidden_blocks += 1;
+                continue;
+            }
+            let marker = match (b_item, a_item) {
+                (None, Some(_)) => '+',
+                (Some(_), None) => '-',
+                (Some(b), Some(a)) if b != a => '~',
+                _ if has_unknown(unknown) => '~',
+                _ => ' ',
+            };
+            let object =
+                |item: Option<&Value>| item.and_then(Value::as_object).cloned().unwrap_or_default();
+            let label = label.map(|label| format!(" \"{}\"", label));
+            output.push('\n');
+            push_line(
+                output,
+                indent,
+                marker,
+                &format!("{}{} {{{}", key, label.unwrap_or_default(), note),
+            );
+            diff_object(
+                output,
+                &object(b_item),
+                &object(a_item),
+                unknown,
+                indent + 4,
+                show_unchanged,
+                annotations.block(key, index, count),
+            );
+            push_closing(output, indent, "}");
+        }
+    }
+    if hidden_blocks > 0 {
+        output.push('\n');
+    }
+    push_hidden(output, indent, hidden_blocks, "block");
+}
+
+/// The blocks of one block type before and after, paired by key for `map`
+/// nesting and by position otherwise, with the key as the block's label.
+fn block_pairs<'a>(
+    nested: Option<&NestedBlock>,
+    before: Option<&'a Value>,
+    after: Option<&'a Value>,
+) -> Vec<(Option<&'a str>, Option<&'a Value>, Option<&'a Value>)> {
+    let items = |value: Option<&'a Value>| -> Vec<(Option<&'a str>, &'a Value)> {
+        match (nested, value) {
+            (_, None) => Vec::new(),
+            (Some(nested), Some(value)) => nested.items(value),
+            (None, Some(value)) => value
+                .as_array()
+                .map(|items| items.iter().map(|item| (None, item)).collect())
+                .unwrap_or_default(),
+        }
+    };
+    let (before, after) = (items(before), items(after));
+    if nested.is_some_and(|nested| nested.nesting_mode == "map") {
+        let mut labels: Vec<Option<&str>> = before
+            .iter()
+            .chain(&after)
+            .map(|(label, _)| *label)
+            .collect();
+        labels.sort();
+        labels.dedup();
+        let find = |items: &[(Option<&'a str>, &'a Value)], label| {
+            items
+                .iter()
+                .find(|(key, _)| *key == label)
+                .map(|(_, item)| *item)
+        };
+        return labels
+            .into_iter()
+            .map(|label| (label, find(&before, label), find(&after, label)))
+            .collect();
+    }
+    (0..before.len().max(after.len()))
+        .map(|i| {
+            (
+                None,
+                before.get(i).map(|item| item.1),
+                after.get(i).map(|item| item.1),
+            )
+        })
+        .collect()
+}
+
+/// Writes `name = value` lines for one attribute or map element. Returns
+/// whether the value was unchanged and hidden.
+fn diff_value(
+    output: &mut String,
+    indent: usize,
+    name: &str,
+    before: Option<&Value>,
+    after: Option<&Value>,
+    show_unchanged: bool,
+) -> bool {
+    match (before, after) {
+        (None, None) => false,
+        (None, Some(a)) => {
+            let value = render_value(a, '+', indent);
+            push_line(output, indent, '+', &format!("{} = {}", name, value));
+            false
+        }
+        (Some(b), None) => {
+            let value = render_value(b, '-', indent);
+            push_line(
+                output,
+                indent,
+                '-',
+                &format!("{} = {} -> null", name, value),
+            );
+            false
+        }
+        (Some(b), Some(a)) if b == a => {
+            if !show_unchanged {
+                return true;
+            }
+            let value = render_value(b, ' ', indent);
+            push_line(output, indent, ' ', &format!("{} = {}", name, value));
+            false
+        }
+        (Some(Value::Object(b)), Some(Value::Object(a))) => {
+            push_line(output, indent, '~', &format!("{} = {{", name));
+            diff_map(output, b, a, indent + 4, show_unchanged);
+            push_closing(output, indent, "}");
+            false
+        }
+        (Some(Value::Array(b)), Some(Value::Array(a))) => {
+            push_line(output, indent, '~', &format!("{} = [", name));
+            diff_list(output, b, a, indent + 4, show_unchanged);
+            push_closing(output, indent, "]");
+            false
+        }
+        (Some(Value::String(b)), Some(Value::String(a)))
+            if b.contains('\n') || a.contains('\n') =>
+        {
+            push_line(output, indent, '~', &format!("{} = <<-EOT", name));
+            for line in diff_lines(b, a, DIFF_CONTEXT_LINES) {
+                match line {
+                    DiffLine::Context(text) => push_line(output, indent + 4, ' ', &text),
+                    DiffLine::Removed(text) => push_line(output, indent + 4, '-', &text),
+                    DiffLine::Added(text) => push_line(output, indent + 4, '+', &text),
+                    DiffLine::Skipped(count) => push_hidden(output, indent + 4, count, "line"),
+                }
+            }
+            push_closing(output, indent, "EOT");
+            false
+        }
+        (Some(b), Some(a)) => {
+            let (b, a) = (render_value(b, '-', indent), render_value(a, '+', indent));
+            push_line(output, indent, '~', &format!("{} = {} -> {}", name, b, a));
+            false
+        }
+    }
+}
+
+fn diff_map(
+    output: &mut String,
+    before: &Map<String, Value>,
+    after: &Map<String, Value>,
+    indent: usize,
+    show_unchanged: bool,
+) {
+    let mut keys: Vec<&String> = before.keys().chain(after.keys()).collect();
+    keys.sort();
+    keys.dedup();
+    let width = keys
+        .iter()
+        .filter(|key| show_unchanged || before.get(key.as_str()) != after.get(key.as_str()))
+        .map(|key| map_key(key).len())
+        .max()
+        .unwrap_or(0);
+
+    let mut hidden = 0;
+    for key in keys {
+        let name = format!("{:width$}", map_key(key), width = width);
+        if diff_value(
+            output,
+            indent,
+            &name,
+            before.get(key.as_str()),
+            after.get(key.as_str()),
+            show_unchanged,
+        ) {
+            hidden += 1;
+        }
+    }
+    push_hidden(output, indent, hidden, "element");
+}
+
+/// Removed elements first, then the new list with additions marked.
+fn diff_list(
+    output: &mut String,
+    before: &[Value],
+    after: &[Value],
+    indent: usize,
+    show_unchanged: bool,
+) {
+    for item in before.iter().filter(|item| !after.contains(item)) {
+        let value = render_value(item, '-', indent);
+        push_line(output, indent, '-', &format!("{},", value));
+    }
+    let mut hidden = 0;
+    for item in after {
+        if !before.contains(item) {
+            let value = render_value(item, '+', indent);
+            push_line(output, indent, '+', &format!("{},", value));
+        } else if show_unchanged {
+            let value = render_value(item, ' ', indent);
+            push_line(output, indent, ' ', &format!("{},", value));
+        } else {
+            hidden += 1;
+        }
+    }
+    push_hidden(output, indent, hidden, "element");
+}
+
+fn map_key(key: &str) -> String {
+    format!("\"{}\"", key)
+}
+
+/// Renders a value starting on a line at `indent`; nested lines of lists and
+/// maps carry `marker`.
+fn render_value(value: &Value, marker: char, indent: usize) -> String {
+    let pad = " ".repeat(indent + 2);
+    match value {
+        Value::Array(items) if !items.is_empty() => {
+            let mut output = String::from("[\n");
+            for item in items {
+                let item = render_value(item, marker, indent + 4);
+                push_line(&mut output, indent + 4, marker, &format!("{},", item));
+            }
+            output.push_str(&format!("{}]", pad));
+            output
+        }
+        Value::Object(map) if !map.is_empty() => {
+            let width = map.keys().map(|key| map_key(key).len()).max().unwrap_or(0);
+            let mut output = String::from("{\n");
+            for (key, item) in map {
+                let item = render_value(item, marker, indent + 4);
+                let name = format!("{:width$}", map_key(key), width = width);
+                push_line(
+                    &mut output,
+                    indent + 4,
+                    marker,
+                    &format!("{} = {}", name, item),
+                );
+            }
+            output.push_str(&format!("{}}}", pad));
+            output
+        }
+        Value::String(text) if text == SENSITIVE_PLACEHOLDER => "(sensitive value)".to_string(),
+        Value::String(text) if text.contains('\n') => {
+            let mut output = String::from("<<-EOT\n");
+            for line in text.lines() {
+                push_line(&mut output, indent + 4, marker, line);
+            }
+            output.push_str(&format!("{}EOT", pad));
+            output
+        }
+        _ => format_value(value),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Change, Configuration, SchemaBlock};
+    use serde_json::json;
+
+    fn change(actions: &[&str], before: Value, after: Value) -> ResourceChange {
+        ResourceChange {
+            address: "aws_instance.web".to_string(),
+            resource_type: "aws_instance".to_string(),
+            name: "web".to_string(),
+            change: Change {
+                actions: actions.iter().map(|a| a.to_string()).collect(),
+                before: Some(before).filter(|v| !v.is_null()),
+                after: Some(after).filter(|v| !v.is_null()),
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_update_block() {
+        let change = change(
+            &["update"],
+            json!({
+                "ami": "ami-1",
+                "instance_type": "t3.micro",
+                "tags": {"Name": "web", "Env": "dev"},
+                "root_block_device": [{"volume_size": 8, "encrypted": true}],
+                "ebs_block_device": [{"device_name": "/dev/sdb"}]
+            }),
+            json!({
+                "ami": "ami-2",
+                "instance_type": "t3.micro",
+                "tags": {"Name": "web", "Env": "prod"},
+                "root_block_device": [{"volume_size": 16, "encrypted": true}],
+                "ebs_block_device": [{"device_name": "/dev/sdb"}]
+            }),
+        );
+
+        assert_eq!(
+            format_hcl_change(
+                &change,
+                false,
+                false,
+                RiskLevel::Low,
+                Annotations::default()
+            ),
+            r#"  # aws_instance.web will be updated in-place
+  ~ resource "aws_instance" "web" {
+      ~ ami  = "ami-1" -> "ami-2"
+      ~ tags = {
+          ~ "Env" = "dev" -> "prod"
+            # (1 unchanged element hidden)
+        }
+        # (1 unchanged attribute hidden)
+
+      ~ root_block_device {
+          ~ volume_size = 8 -> 16
+            # (1 unchanged attribute hidden)
+        }
+
+        # (1 unchanged block hidden)
+    }
+
+"#
+        );
+    }
+
+    #[test]
+    fn test_create_and_destroy() {
+        let create = change(
+            &["create"],
+            Value::Null,
+            json!({"ami": "ami-1", "security_groups": ["a", "b"], "user_data": null}),
+        );
+        assert_eq!(
+            format_hcl_block(&create, false, Annotations::default()),
+            r#"  + resource "aws_instance" "web" {
+      + ami             = "ami-1"
+      + security_groups = [
+          + "a",
+          + "b",
+        ]
+    }
+"#
+        );
+
+        let destroy = change(&["delete"], json!({"ami": "ami-1"}), Value::Null);
+        assert_eq!(
+            format_hcl_block(&destroy, false, Annotations::default()),
+            "  - resource \"aws_instance\" \"web\" {\n      - ami = \"ami-1\" -> null\n    }\n"
+        );
+    }
+
+    #[test]
+    fn test_known_after_apply() {
+        let mut replace = change(
+            &["delete", "create"],
+            json!({"ami": "ami-1", "id": "i-123", "root_block_device": [{"volume_id": "vol-1"}]}),
+            json!({"ami": "ami-2", "root_block_device": [{}]}),
+        );
+        replace.change.after_unknown =
+            json!({"id": true, "root_block_device": [{"volume_id": true}]});
+        assert_eq!(
+            format_hcl_block(&replace, false, Annotations::default()),
+            r#"-/+ resource "aws_instance" "web" {
+      ~ ami = "ami-1" -> "ami-2"
+      ~ id  = "i-123" -> (known after apply)
+
+      ~ root_block_device {
+          ~ volume_id = "vol-1" -> (known after apply)
+        }
+    }
+"#
+        );
+
+        let mut create = change(&["create"], Value::Null, json!({"ami": "ami-1"}));
+        create.change.after_unknown = json!({"arn": true, "id": true});
+        assert_eq!(
+            format_hcl_block(&create, false, Annotations::default()),
+            r#"  + resource "aws_instance" "web" {
+      + ami = "ami-1"
+      + arn = (known after apply)
+      + id  = (known after apply)
+    }
+"#
+        );
+    }
+
+    #[test]
+    fn test_show_unchanged_and_heredoc() {
+        let change = change(
+            &["delete", "create"],
+            json!({"ami": "ami-1", "user_data": "#!/bin/bash\necho old\n"}),
+            json!({"ami": "ami-1", "user_data": "#!/bin/bash\necho new\n"}),
+        );
+        assert_eq!(
+            format_hcl_block(&change, true, Annotations::default()),
+            r#"-/+ resource "aws_instance" "web" {
+        ami       = "ami-1"
+      ~ user_data = <<-EOT
+            #!/bin/bash
+          - echo old
+          + echo new
+        EOT
+    }
+"#
+        );
+    }
+
+    #[test]
+    fn test_schema_blocks_and_notes() {
+        let schema: SchemaBlock = serde_json::from_value(json!({
+            "attributes": {
+                "ingress": {"type": ["list", ["object", {"port": "number"}]], "optional": true},
+                "arn": {"type": "string", "computed": true},
+                "iops": {"type": "number", "optional": true, "deprecated": true}
+            },
+            "block_types": {
+                "timeouts": {"nesting_mode": "single", "block": {"attributes": {}}},
+                "network": {"nesting_mode": "map", "block": {"attributes": {}}}
+            }
+        }))
+        .unwrap();
+        let change = change(
+            &["update"],
+            json!({
+                "ingress": [{"port": 80}],
+                "arn": "a",
+                "iops": 100,
+                "timeouts": {"create": "10m"},
+                "network": {"eth0": {"subnet": "a"}}
+            }),
+            json!({
+                "ingress": [{"port": 443}],
+                "arn": "b",
+                "iops": 200,
+                "timeouts": {"create": "20m"},
+                "network": {"eth0": {"subnet": "b"}}
+            }),
+        );
+        assert_eq!(
+            format_hcl_block(
+                &change,
+                false,
+                Annotations {
+                    schema: Some(&schema),
+                    ..Default::default()
+                }
+            ),
+            r#"  ~ resource "aws_instance" "web" {
+      ~ arn     = "a" -> "b" # computed
+      ~ ingress = [
+          - {
+              - "port" = 80
+            },
+          + {
+              + "port" = 443
+            },
+        ]
+      ~ iops    = 100 -> 200 # deprecated
+
+      ~ network "eth0" {
+          ~ subnet = "a" -> "b"
+        }
+
+      ~ timeouts {
+          ~ create = "10m" -> "20m"
+        }
+    }
+"#
+        );
+    }
+
+    #[test]
+    fn test_expression_sources() {
+        let configuration: Configuration = serde_json::from_value(json!({
+            "root_module": {
+                "resources": [{
+                    "address": "aws_instance.web",
+                    "mode": "managed",
+                    "type": "aws_instance",
+                    "name": "web",
+                    "expressions": {
+                        "instance_type": {"references": ["var.instance_type"]},
+                        "root_block_device": [{"volume_size": {"references": ["local.disk_size"]}}],
+                        "ebs_block_device": [{"volume_size": {"references": ["local.ebs_size"]}}],
+                        "network_interface": [{"device_index": {"references": ["local.index"]}}]
+                    }
+                }]
+            }
+        }))
+        .unwrap();
+        let schema: SchemaBlock = serde_json::from_value(json!({
+            "block_types": {
+                "root_block_device": {"nesting_mode": "list", "block": {"attributes": {}}},
+                "ebs_block_device": {"nesting_mode": "set", "block": {"attributes": {}}},
+                "network_interface": {"nesting_mode": "list", "block": {"attributes": {}}}
+            }
+        }))
+        .unwrap();
+        let change = change(
+            &["update"],
+            json!({
+                "instance_type": "t3.micro",
+                "root_block_device": [{"volume_size": 8}],
+                "ebs_block_device": [{"volume_size": 8}],
+                "network_interface": [{"device_index": 0}]
+            }),
+            json!({
+                "instance_type": "t3.large",
+                "root_block_device": [{"volume_size": 16}],
+                "ebs_block_device": [{"volume_size": 16}],
+                "network_interface": [{"device_index": 1}, {"device_index": 2}]
+            }),
+        );
+        let sources = configuration.sources(&change);
+        let annotations = Annotations {
+            schema: Some(&schema),
+            sources: sources.as_ref(),
+            ..Default::default()
+        };
+        assert_eq!(
+            format_hcl_block(&change, false, annotations),
+            r#"  ~ resource "aws_instance" "web" {
+      ~ instance_type = "t3.micro" -> "t3.large" <- var.instance_type
+
+      ~ ebs_block_device {
+          ~ volume_size = 8 -> 16
+        }
+
+      ~ network_interface {
+          ~ device_index = 0 -> 1
+        }
+
+      + network_interface {
+          + device_index = 2
+        }
+
+      ~ root_block_device {
+          ~ volume_size = 8 -> 16 <- local.disk_size
+        }
+    }
+"#
+        );
+    }
+
+    #[test]
+    fn test_resource_name_fallback() {
+        let mut change = change(&["create"], Value::Null, json!({}));
+        change.name.clear();
+        change.address = "module.app.aws_instance.web[\"a.b\"]".to_string();
+        assert_eq!(resource_name(&change), "web");
+        assert_eq!("terraform".parse::<RenderStyle>(), Ok(RenderStyle::Hcl));
+    }
+}
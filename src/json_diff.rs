@@ -0,0 +1,186 @@
+use serde_json::Value;
+
+/// One difference between two decoded JSON documents.
+#[derive(Debug, Clone, PartialEq)]
+pub enum JsonChange {
+    Changed {
+        path: String,
+        before: Value,
+        after: Value,
+    },
+    Added {
+        path: String,
+        value: Value,
+    },
+    Removed {
+        path: String,
+        value: Value,
+    },
+}
+
+impl JsonChange {
+    pub fn path(&self) -> &str {
+        match self {
+            JsonChange::Changed { path, .. }
+            | JsonChange::Added { path, .. }
+            | JsonChange::Removed { path, .. } => path,
+        }
+    }
+
+    /// `path: old => new`, `+ path: value` or `- path: value`.
+    pub fn format(&self) -> String {
+        match self {
+            JsonChange::Changed {
+                path,
+                before,
+                after,
+            } => format!("{}: {} => {}", path, before, after),
+            JsonChange::Added { path, value } => format!("+ {}: {}", path, value),
+            JsonChange::Removed { path, value } => format!("- {}: {}", path, value),
+        }
+    }
+}
+
+/// Result of comparing two JSON-encoded string attributes.
+#[derive(Debug, Clone, PartialEq)]
+pub enum JsonStringDiff {
+    /// The documents are equal once decoded; only whitespace or key order changed.
+    Cosmetic,
+    Changes(Vec<JsonChange>),
+}
+
+/// Label used when the documents differ at the top level.
+const ROOT_PATH: &str = "(document)";
+
+/// Decodes a string attribute holding a JSON object or array.
+pub fn decode_json_string(value: &Value) -> Option<Value> {
+    let text = value.as_str()?.trim();
+    if !(text.starts_with('{') || text.starts_with('[')) {
+        return None;
+    }
+    serde_json::from_str(text).ok()
+}
+
+/// Compares two string attributes as JSON documents. Returns `None` unless
+/// both decode to a JSON object or array.
+pub fn json_string_diff(before: &Value, after: &Value) -> Option<JsonStringDiff> {
+    let before = decode_json_string(before)?;
+    let after = decode_json_string(after)?;
+
+    if before == after {
+        return Some(JsonStringDiff::Cosmetic);
+    }
+    let mut changes = Vec::new();
+    diff_values("", &before, &after, &mut changes);
+    Some(JsonStringDiff::Changes(changes))
+}
+
+fn join_key(path: &str, key: &str) -> String {
+    if path.is_empty() {
+        key.to_string()
+    } else {
+        format!("{}.{}", path, key)
+    }
+}
+
+fn display_path(path: &str) -> String {
+    if path.is_empty() {
+        ROOT_PATH.to_string()
+    } else {
+        path.to_string()
+    }
+}
+
+fn diff_values(path: &str, before: &Value, after: &Value, changes: &mut Vec<JsonChange>) {
+    match (before, after) {
+        (Value::Object(b), Value::Object(a)) => {
+            for (key, b_val) in b {
+                let child = join_key(path, key);
+                match a.get(key) {
+                    Some(a_val) => diff_values(&child, b_val, a_val, changes),
+                    None => changes.push(JsonChange::Removed {
+                        path: child,
+                        value: b_val.clone(),
+                    }),
+                }
+            }
+            for (key, a_val) in a {
+                if !b.contains_key(key) {
+                    changes.push(JsonChange::Added {
+                        path: join_key(path, key),
+                        value: a_val.clone(),
+                    });
+                }
+            }
+        }
+        (Value::Array(b), Value::Array(a)) => {
+            for i in 0..b.len().max(a.len()) {
+                let child = format!("{}[{}]", path, i);
+                match (b.get(i), a.get(i)) {
+                    (Some(b_val), Some(a_val)) => diff_values(&child, b_val, a_val, changes),
+                    (Some(b_val), None) => changes.push(JsonChange::Removed {
+                        path: child,
+                        value: b_val.clone(),
+                    }),
+                    (None, Some(a_val)) => changes.push(JsonChange::Added {
+                        path: child,
+                        value: a_val.clone(),
+                    }),
+                    (None, None) => {}
+                }
+            }
+        }
+        _ if before != after => changes.push(JsonChange::Changed {
+            path: display_path(path),
+            before: before.clone(),
+            after: after.clone(),
+        }),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_cosmetic_only() {
+        let before = json!(r#"{"Version": "2012-10-17", "Statement": []}"#);
+        let after = json!("{\n  \"Statement\": [],\n  \"Version\": \"2012-10-17\"\n}");
+
+        assert_eq!(
+            json_string_diff(&before, &after),
+            Some(JsonStringDiff::Cosmetic)
+        );
+    }
+
+    #[test]
+    fn test_structural_changes() {
+        let before =
+            json!(r#"{"Statement": [{"Effect": "Allow", "Action": "s3:GetObject", "Sid": "a"}]}"#);
+        let after = json!(
+            r#"{"Statement": [{"Effect": "Allow", "Action": ["s3:GetObject", "s3:PutObject"]}, {"Effect": "Deny"}]}"#
+        );
+
+        let Some(JsonStringDiff::Changes(changes)) = json_string_diff(&before, &after) else {
+            panic!("expected structural changes");
+        };
+        let formatted: Vec<String> = changes.iter().map(JsonChange::format).collect();
+        assert_eq!(
+            formatted,
+            vec![
+                r#"Statement[0].Action: "s3:GetObject" => ["s3:GetObject","s3:PutObject"]"#,
+                r#"- Statement[0].Sid: "a""#,
+                r#"+ Statement[1]: {"Effect":"Deny"}"#,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_not_json() {
+        assert_eq!(json_string_diff(&json!("plain"), &json!("{}")), None);
+        assert_eq!(json_string_diff(&json!("{broken"), &json!("{}")), None);
+        assert_eq!(json_string_diff(&json!(1), &json!(2)), None);
+    }
+}
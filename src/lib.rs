@@ -1,73 +1,444 @@
 use serde::Deserialize;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::time::SystemTime;
 
-#[derive(Deserialize, Debug, Clone)]
+mod annotations;
+mod apply;
+mod checks;
+mod config;
+mod configuration;
+mod deferred;
+mod diagnostics;
+mod diff;
+mod drift;
+mod hcl;
+mod json_diff;
+mod markdown;
+mod metadata;
+mod moves;
+mod noise;
+mod plan_diff;
+mod risk;
+mod schema;
+mod sort;
+mod state;
+mod stats;
+mod stream;
+mod unchanged;
+mod variables;
+mod walk;
+
+pub use annotations::Annotations;
+pub use apply::{format_elapsed, ApplyProgress, ApplyStatus, ResourceProgress};
+pub use checks::{
+    count_checks, format_check_counts, format_checks, CheckAddress, CheckInstance, CheckProblem,
+    CheckResult, CheckStatus,
+};
+pub use config::{find_config_file, Config, OutputFormat, CONFIG_FILE_NAME};
+pub use configuration::{
+    ConfigExpression, ConfigModule, ConfigResource, Configuration, Expression, ExpressionSources,
+    ModuleCall, VariableDeclaration,
+};
+pub use deferred::{format_deferred_counts, DeferredChange};
+pub use diagnostics::{
+    format_diagnostics, format_diagnostics_markdown, Diagnostic, ExpressionValue, Severity,
+    Snippet, SnippetLine, SourcePos, SourceRange,
+};
+pub use diff::{
+    diff_lines, format_line_diff, format_skipped, is_multiline_change, DiffLine, DIFF_CONTEXT_LINES,
+};
+pub use drift::{drift_for, RelevantAttribute};
+pub use hcl::{format_hcl_block, RenderStyle};
+pub use json_diff::{decode_json_string, json_string_diff, JsonChange, JsonStringDiff};
+pub use markdown::{format_plan_markdown, html_escape};
+pub use metadata::{format_duration, parse_duration, PlanMetadata};
+pub use moves::{detect_probable_moves, ProbableMove};
+pub use noise::{normalized_eq, IgnorePath, NoiseFilter, NoiseReport};
+pub use plan_diff::{diff_plans, format_plan_diff, PlanDiffEntry};
+pub use risk::{RiskClassifier, RiskLevel, RiskReport, RiskRule};
+pub use schema::{
+    NestedBlock, ProviderSchema, ProviderSchemas, Schema, SchemaAttribute, SchemaBlock, SchemaIndex,
+};
+pub use sort::{sort_changes, SortOrder};
+pub use state::{
+    format_state_summary, StateModule, StateResource, StateValues, TerraformState,
+    SENSITIVE_PLACEHOLDER,
+};
+pub use stats::{short_provider_name, ActionCounts, PlanStats};
+pub use stream::{is_plan_stream, parse_plan_stream};
+pub use unchanged::Unchanged;
+pub use variables::{InputVariable, PlanVariable};
+pub use walk::{ChangeView, PlanChanges};
+
+#[derive(Deserialize, Debug, Clone, Default)]
 pub struct TerraformPlan {
     pub resource_changes: Vec<ResourceChange>,
+    /// Objects that changed outside of Terraform since the last apply.
+    #[serde(default)]
+    pub resource_drift: Vec<ResourceChange>,
+    /// The attributes of drifted objects that the planned changes depend on.
+    #[serde(default)]
+    pub relevant_attributes: Vec<RelevantAttribute>,
+    #[serde(default)]
+    pub output_changes: BTreeMap<String, Change>,
+    #[serde(flatten)]
+    pub metadata: PlanMetadata,
+    /// Input variable values the plan was made with.
+    #[serde(default)]
+    pub variables: BTreeMap<String, PlanVariable>,
+    #[serde(default)]
+    pub configuration: Configuration,
+    /// Results of `check` blocks and of resource, output and variable
+    /// conditions.
+    #[serde(default)]
+    pub checks: Vec<CheckResult>,
+    /// Changes Terraform could not plan yet, making this plan partial.
+    #[serde(default)]
+    pub deferred_changes: Vec<DeferredChange>,
+    /// Warnings and errors from the `-json` UI stream.
+    #[serde(default)]
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl TerraformPlan {
+    /// Whether a check block or condition failed or could not be evaluated.
+    pub fn has_failed_checks(&self) -> bool {
+        self.checks.iter().any(|check| check.status.is_failure())
+    }
+
+    /// Whether Terraform reported an error while planning.
+    pub fn has_errors(&self) -> bool {
+        self.metadata.errored || self.diagnostics.iter().any(Diagnostic::is_error)
+    }
+
+    /// The diagnostics to show, errors first. A plan document only says that
+    /// planning errored, so that is reported as a generic error.
+    pub fn reported_diagnostics(&self) -> Vec<Diagnostic> {
+        let mut diagnostics = self.diagnostics.clone();
+        diagnostics.sort_by_key(|diagnostic| !diagnostic.is_error());
+        if self.metadata.errored && !diagnostics.iter().any(Diagnostic::is_error) {
+            diagnostics.insert(
+                0,
+                Diagnostic {
+                    severity: Severity::Error,
+                    summary: "Planning failed".to_string(),
+                    detail: "Terraform reported errors while planning, so this plan is \
+                             incomplete. Run terraform plan again to see them."
+                        .to_string(),
+                    ..Default::default()
+                },
+            );
+        }
+        diagnostics
+    }
 }
 
-#[derive(Deserialize, Debug, Clone)]
+#[derive(Deserialize, Debug, Clone, Default)]
 pub struct ResourceChange {
     pub address: String,
+    /// `managed` for resources, `data` for data sources.
+    #[serde(default)]
+    pub mode: String,
+    #[serde(rename = "type", default)]
+    pub resource_type: String,
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub provider_name: String,
+    #[serde(default)]
+    pub module_address: Option<String>,
+    #[serde(default)]
+    pub previous_address: Option<String>,
+    #[serde(default)]
+    pub action_reason: Option<String>,
+    /// Key of a deposed object left behind by a failed create-before-destroy.
+    #[serde(default)]
+    pub deposed: Option<String>,
     pub change: Change,
 }
 
-#[derive(Deserialize, Debug, Clone)]
+impl ResourceChange {
+    /// Whether Terraform moved this object from another address.
+    pub fn is_moved(&self) -> bool {
+        self.previous_address
+            .as_ref()
+            .is_some_and(|previous| previous != &self.address)
+    }
+
+    /// `old -> new` for moved resources, `address (deposed object key)` for
+    /// deposed objects, the address otherwise.
+    pub fn display_address(&self) -> String {
+        match (&self.previous_address, &self.deposed) {
+            (_, Some(key)) => format!("{} (deposed object {})", self.address, key),
+            (Some(previous), _) if self.is_moved() => {
+                format!("{} -> {}", previous, self.address)
+            }
+            _ => self.address.clone(),
+        }
+    }
+
+    /// Whether this is a data source Terraform will read during apply.
+    pub fn is_data_read(&self) -> bool {
+        get_action(&self.change.actions) == "read"
+    }
+
+    /// Why a data source is read during apply instead of during planning,
+    /// in Terraform's wording.
+    pub fn read_reason(&self) -> Option<&'static str> {
+        match self.action_reason.as_deref()? {
+            "read_because_config_unknown" => Some("config refers to values not yet known"),
+            "read_because_dependency_pending" => {
+                Some("depends on a resource or a module with changes pending")
+            }
+            "read_because_check_nested" => Some("nested within a check block"),
+            _ => None,
+        }
+    }
+
+    /// Describes an object in `resource_drift`, e.g.
+    /// `~ aws_instance.web has changed (ami, tags)`.
+    pub fn format_drift(&self) -> String {
+        if get_action(&self.change.actions) == "delete" {
+            return format!("- {} has been deleted", self.address);
+        }
+        let attributes = changed_attributes(&self.change);
+        if attributes.is_empty() {
+            format!("~ {} has changed", self.address)
+        } else {
+            format!("~ {} has changed ({})", self.address, attributes.join(", "))
+        }
+    }
+
+    /// Whether this object is being imported by an `import` block.
+    pub fn is_importing(&self) -> bool {
+        self.change.importing.is_some()
+    }
+
+    /// Describes what happens to the object, e.g. `has moved`,
+    /// `will be imported (id "i-123")` or `has moved and will be <action>`,
+    /// using the given wording for the action.
+    pub fn describe(&self, action_text: &str) -> String {
+        let mut clauses = Vec::new();
+        if self.is_moved() {
+            clauses.push("has moved".to_string());
+        }
+        if let Some(importing) = &self.change.importing {
+            clauses.push(match &importing.id {
+                Some(id) => format!("will be imported (id \"{}\")", id),
+                None => "will be imported".to_string(),
+            });
+        }
+        if clauses.is_empty() || get_action(&self.change.actions) != "unknown" {
+            clauses.push(format!("will be {}", action_text));
+        }
+        clauses.join(" and ")
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, Default)]
 pub struct Change {
     pub actions: Vec<String>,
     pub before: Option<serde_json::Value>,
     pub after: Option<serde_json::Value>,
+    #[serde(default)]
+    pub importing: Option<Importing>,
+    #[serde(default)]
+    pub generated_config: Option<String>,
+    /// `true` when the whole value is sensitive, or an object marking the
+    /// sensitive attributes.
+    #[serde(default)]
+    pub before_sensitive: serde_json::Value,
+    #[serde(default)]
+    pub after_sensitive: serde_json::Value,
+    /// `true` when the whole value is only known after apply.
+    #[serde(default)]
+    pub after_unknown: serde_json::Value,
+}
+
+#[derive(Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct Importing {
+    #[serde(default)]
+    pub id: Option<String>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct FormatOptions {
+    pub collapsed: bool,
+    pub risk: RiskClassifier,
+    pub sort: SortOrder,
+    /// Append per type, provider and module breakdown tables.
+    pub stats: bool,
+    /// Show the HCL Terraform generated for imported resources.
+    pub generated_config: bool,
+    /// The plan was built from a state snapshot with [`TerraformState::to_plan`]
+    /// and is rendered as a list of resources rather than changes.
+    pub state: bool,
+    /// Hide cosmetic differences before rendering.
+    pub noise: Option<NoiseFilter>,
+    /// List the attributes an update leaves unchanged instead of counting them.
+    pub show_unchanged: bool,
+    pub style: RenderStyle,
+    /// Warn about plans older than this many seconds.
+    pub max_age: Option<u64>,
+    /// Treat failed checks as an error, failing the command.
+    pub fail_on_checks: bool,
+    /// Show every object that changed outside of Terraform, not only those
+    /// the planned changes depend on.
+    pub all_drift: bool,
+    /// Provider schemas, to lay out nested blocks, note computed and
+    /// deprecated attributes and warn when deprecated ones are set.
+    pub schema: Option<SchemaIndex>,
 }
 
 pub fn format_plan(plan: &TerraformPlan, collapsed: bool) -> String {
-    let mut output = String::new();
-    let mut counts = HashMap::new();
+    let options = FormatOptions {
+        collapsed,
+        ..Default::default()
+    };
+    format_plan_with_options(plan, &options)
+}
 
-    for change in &plan.resource_changes {
-        let action = get_action(&change.change.actions);
-        *counts.entry(action).or_insert(0) += 1;
-        output.push_str(&format_resource_change(change, collapsed));
+/// Applies the noise filter, if enabled, and adds warnings for deprecated
+/// attributes when a schema is loaded, before a renderer walks the plan.
+pub fn prepare_plan(plan: &TerraformPlan, options: &FormatOptions) -> (TerraformPlan, NoiseReport) {
+    let (mut plan, noise) = match &options.noise {
+        Some(filter) => filter.apply(plan, options.schema.as_ref()),
+        None => (plan.clone(), NoiseReport::default()),
+    };
+    if let Some(schema) = &options.schema {
+        schema.annotate(&mut plan);
+    }
+    (plan, noise)
+}
+
+pub fn format_plan_with_options(plan: &TerraformPlan, options: &FormatOptions) -> String {
+    if options.state {
+        return state::format_state(plan, options);
+    }
+
+    let (filtered, noise) = prepare_plan(plan, options);
+    let plan = &filtered;
+
+    let report = options.risk.assess(plan);
+    let mut output = plan
+        .metadata
+        .format_header(options.max_age, SystemTime::now());
+    output.push_str(&variables::format_variables(
+        &plan.input_variables(),
+        options.collapsed,
+    ));
+    output.push_str(&report.format_header());
+    output.push_str(&format_resource_drift(&plan.drift(options.all_drift)));
+
+    let walk = PlanChanges::new(plan, options);
+    for view in &walk.changes {
+        let change = view.change;
+        let annotations = view.annotations();
+        let mut block = match options.style {
+            RenderStyle::Compact => {
+                format_resource_change(change, options.collapsed, view.risk, annotations)
+            }
+            RenderStyle::Hcl => hcl::format_hcl_change(
+                change,
+                options.collapsed,
+                options.show_unchanged,
+                view.risk,
+                annotations,
+            ),
+        };
+        if let Some(drifted) = annotations.drift {
+            let end = block.find('\n').unwrap_or(block.len());
+            block.insert_str(end, &format!(" ({})", drifted.drift_note()));
+        }
+        if !options.collapsed {
+            block.pop();
+            if options.style == RenderStyle::Compact {
+                block.push_str(&format_unchanged(&change.change, options.show_unchanged));
+            }
+            if options.generated_config {
+                block.push_str(&format_generated_config(&change.change));
+            }
+            block.push('\n');
+        }
+        output.push_str(&block);
     }
 
-    output.push_str(&format_summary(&counts));
+    output.push_str(&format_data_reads(&walk.reads));
+    output.push_str(&deferred::format_deferred(&plan.deferred_changes));
+    output.push_str(&format_output_changes(&plan.output_changes));
+    output.push_str(&format_probable_moves(&detect_probable_moves(plan)));
+    output.push_str(&format_diagnostics(&plan.reported_diagnostics()));
+    output.push_str(&format_checks(&plan.checks));
+
+    if options.stats {
+        output.push_str(&PlanStats::from_plan(plan).format_text());
+    } else {
+        output.push_str(&format_summary(&walk.counts));
+    }
+    output.push_str(&noise.format());
+    output.push_str(&report.format_score());
     output
 }
 
-fn get_action(actions: &[String]) -> &'static str {
+pub fn get_action(actions: &[String]) -> &'static str {
     match actions {
         [action] if action == "create" => "create",
         [action] if action == "update" => "update",
         [action] if action == "delete" => "delete",
         [a1, a2] if a1 == "delete" && a2 == "create" => "replace",
+        [action] if action == "read" => "read",
         _ => "unknown",
     }
 }
 
-fn format_resource_change(change: &ResourceChange, collapsed: bool) -> String {
+fn format_resource_change(
+    change: &ResourceChange,
+    collapsed: bool,
+    risk: RiskLevel,
+    annotations: Annotations,
+) -> String {
     let action = get_action(&change.change.actions);
-    let (symbol, _color) = match action {
-        "create" => ("+", "green"),
-        "update" => ("~", "yellow"),
-        "delete" => ("-", "red"),
-        "replace" => ("-/+", "yellow"),
-        _ => ("?", "white"),
-    };
+    let symbol = walk::change_symbol(change);
 
     let indicator = if collapsed { "▶" } else { "▼" };
     let mut output = format!(
-        "{} {} {} will be {}\n",
-        indicator, symbol, change.address, action
+        "{} {} {} {}",
+        indicator,
+        symbol,
+        change.display_address(),
+        change.describe(action)
     );
+    if risk.is_high() {
+        output.push_str(&format!(" [{}]", risk.as_str().to_uppercase()));
+    }
+    output.push('\n');
 
     if !collapsed {
-        output.push_str(&format_changes(&change.change, action));
+        output.push_str(&format_changes(&change.change, action, annotations));
     }
     output.push('\n');
     output
 }
 
-fn format_changes(change: &Change, action: &str) -> String {
+/// The marker the text output uses for an action returned by [`get_action`].
+pub fn action_symbol(action: &str) -> &'static str {
+    match action {
+        "create" => "+",
+        "update" => "~",
+        "delete" => "-",
+        "replace" => "-/+",
+        "read" => "<=",
+        _ => "?",
+    }
+}
+
+fn format_changes(change: &Change, action: &str, annotations: Annotations) -> String {
     let mut output = String::new();
+    let push = |output: &mut String, key: &str, text: String| {
+        let start = output.len();
+        output.push_str(&text);
+        append_to_line(output, start, &annotations.suffix(key));
+    };
 
     if action == "update" || action == "replace" {
         if let (Some(before), Some(after)) = (&change.before, &change.after) {
@@ -75,15 +446,12 @@ fn format_changes(change: &Change, action: &str) -> String {
                 for (key, after_val) in after_obj {
                     if let Some(before_val) = before_obj.get(key) {
                         if before_val != after_val {
-                            output.push_str(&format!(
-                                "        {}: {} => {}\n",
-                                key,
-                                format_value(before_val),
-                                format_value(after_val)
-                            ));
+                            let text = format_attribute_change(key, before_val, after_val);
+                            push(&mut output, key, text);
                         }
                     } else {
-                        output.push_str(&format!("        {}: {}\n", key, format_value(after_val)));
+                        let text = format!("        {}: {}\n", key, format_value(after_val));
+                        push(&mut output, key, text);
                     }
                 }
             }
@@ -92,7 +460,11 @@ fn format_changes(change: &Change, action: &str) -> String {
         if let Some(after) = &change.after {
             if let Some(after_obj) = after.as_object() {
                 for (key, val) in after_obj {
-                    output.push_str(&format!("        {}: {}\n", key, format_value(val)));
+                    push(
+                        &mut output,
+                        key,
+                        format!("        {}: {}\n", key, format_value(val)),
+                    );
                 }
             }
         }
@@ -100,6 +472,225 @@ fn format_changes(change: &Change, action: &str) -> String {
     output
 }
 
+/// Appends `suffix` to the first line written to `output` since `start`.
+pub(crate) fn append_to_line(output: &mut String, start: usize, suffix: &str) {
+    let end = output[start..]
+        .find('\n')
+        .map_or(output.len(), |offset| start + offset);
+    output.insert_str(end, suffix);
+}
+
+fn format_attribute_change(
+    key: &str,
+    before: &serde_json::Value,
+    after: &serde_json::Value,
+) -> String {
+    if let Some(json_diff) = json_string_diff(before, after) {
+        return format_json_string_diff(key, &json_diff);
+    }
+    if is_multiline_change(before, after) {
+        return format!("        {}:\n{}", key, format_multiline_diff(before, after));
+    }
+    format!(
+        "        {}: {} => {}\n",
+        key,
+        format_value(before),
+        format_value(after)
+    )
+}
+
+fn format_json_string_diff(key: &str, diff: &JsonStringDiff) -> String {
+    match diff {
+        JsonStringDiff::Cosmetic => format!(
+            "        {}: (JSON formatting only, no semantic change)\n",
+            key
+        ),
+        JsonStringDiff::Changes(changes) => {
+            let mut output = format!("        {} (JSON):\n", key);
+            for change in changes {
+                output.push_str(&format!("            {}\n", change.format()));
+            }
+            output
+        }
+    }
+}
+
+fn format_multiline_diff(before: &serde_json::Value, after: &serde_json::Value) -> String {
+    let lines = diff_lines(
+        before.as_str().unwrap_or_default(),
+        after.as_str().unwrap_or_default(),
+        DIFF_CONTEXT_LINES,
+    );
+    format_line_diff(&lines, "            ")
+}
+
+fn format_unchanged(change: &Change, expand: bool) -> String {
+    let unchanged = Unchanged::from_change(change);
+    if expand {
+        unchanged
+            .attributes
+            .iter()
+            .map(|(key, value)| format!("        {}: {}\n", key, format_value(value)))
+            .collect()
+    } else {
+        unchanged
+            .format_counts()
+            .iter()
+            .map(|line| format!("        {}\n", line))
+            .collect()
+    }
+}
+
+fn format_generated_config(change: &Change) -> String {
+    let Some(config) = &change.generated_config else {
+        return String::new();
+    };
+    let mut output = String::from("        # generated configuration:\n");
+    for line in config.lines() {
+        output.push_str(&format!("        {}\n", line));
+    }
+    output
+}
+
+fn format_data_reads(reads: &[&ResourceChange]) -> String {
+    if reads.is_empty() {
+        return String::new();
+    }
+    let mut output = String::from("Data sources to be read during apply:\n");
+    for change in reads {
+        output.push_str(&format!("    <= {}", change.address));
+        if let Some(reason) = change.read_reason() {
+            output.push_str(&format!(" ({})", reason));
+        }
+        output.push('\n');
+    }
+    output.push('\n');
+    output
+}
+
+fn format_resource_drift(drift: &[ResourceChange]) -> String {
+    if drift.is_empty() {
+        return String::new();
+    }
+    let mut output = String::from("Objects changed outside of Terraform:\n");
+    for change in drift {
+        output.push_str(&format!("    {}\n", change.format_drift()));
+    }
+    output.push('\n');
+    output
+}
+
+/// Describes a change to a root module output, e.g. `~ ip: "a" => "b"`, or
+/// `None` when the output does not change. Outputs from the `-json` UI
+/// stream carry no values and are listed by name.
+pub fn format_output_change(name: &str, change: &Change) -> Option<String> {
+    let before = output_value(
+        change.before.as_ref(),
+        &change.before_sensitive,
+        &serde_json::Value::Null,
+    );
+    let after = output_value(
+        change.after.as_ref(),
+        &change.after_sensitive,
+        &change.after_unknown,
+    );
+    let line = match get_action(&change.actions) {
+        "create" => match after {
+            Some(after) => format!("+ {} = {}", name, after),
+            None => format!("+ {}", name),
+        },
+        "update" | "replace" => match (before, after) {
+            (Some(before), Some(after)) => format!("~ {}: {} => {}", name, before, after),
+            (None, Some(after)) => format!("~ {} = {}", name, after),
+            _ => format!("~ {}", name),
+        },
+        "delete" => format!("- {}", name),
+        _ => return None,
+    };
+    Some(line)
+}
+
+/// Whether any part of a value is marked in its part of `after_unknown`.
+fn has_unknown(unknown: &serde_json::Value) -> bool {
+    match unknown {
+        serde_json::Value::Bool(unknown) => *unknown,
+        serde_json::Value::Array(items) => items.iter().any(has_unknown),
+        serde_json::Value::Object(map) => map.values().any(has_unknown),
+        _ => false,
+    }
+}
+
+fn output_value(
+    value: Option<&serde_json::Value>,
+    sensitive: &serde_json::Value,
+    unknown: &serde_json::Value,
+) -> Option<String> {
+    if sensitive == &serde_json::Value::Bool(true) {
+        return Some(SENSITIVE_PLACEHOLDER.to_string());
+    }
+    if unknown == &serde_json::Value::Bool(true) {
+        return Some("(known after apply)".to_string());
+    }
+    value.map(format_value)
+}
+
+fn format_output_changes(outputs: &BTreeMap<String, Change>) -> String {
+    let lines: Vec<String> = outputs
+        .iter()
+        .filter_map(|(name, change)| format_output_change(name, change))
+        .collect();
+    if lines.is_empty() {
+        return String::new();
+    }
+    let mut output = String::from("Changes to Outputs:\n");
+    for line in lines {
+        output.push_str(&format!("    {}\n", line));
+    }
+    output.push('\n');
+    output
+}
+
+fn format_probable_moves(moves: &[ProbableMove]) -> String {
+    if moves.is_empty() {
+        return String::new();
+    }
+    let mut output = String::from("Probable missing moved blocks:\n");
+    for probable in moves {
+        output.push_str(&format!("    {}\n", probable.format()));
+    }
+    output.push('\n');
+    output
+}
+
+/// Top-level attribute names whose value differs between `before` and `after`.
+pub fn changed_attributes(change: &Change) -> Vec<String> {
+    let empty = serde_json::Map::new();
+    let before = change
+        .before
+        .as_ref()
+        .and_then(|v| v.as_object())
+        .unwrap_or(&empty);
+    let after = change
+        .after
+        .as_ref()
+        .and_then(|v| v.as_object())
+        .unwrap_or(&empty);
+
+    let mut keys: Vec<String> = after
+        .iter()
+        .filter(|(key, val)| before.get(*key) != Some(val))
+        .map(|(key, _)| key.clone())
+        .chain(
+            before
+                .keys()
+                .filter(|key| !after.contains_key(*key))
+                .cloned(),
+        )
+        .collect();
+    keys.sort();
+    keys
+}
+
 fn format_value(value: &serde_json::Value) -> String {
     match value {
         serde_json::Value::String(s) => format!("\"{}\"", s),
@@ -115,14 +706,16 @@ fn format_summary(counts: &HashMap<&str, i32>) -> String {
     let update_count = counts.get("update").unwrap_or(&0);
     let replace_count = counts.get("replace").unwrap_or(&0);
     let delete_count = counts.get("delete").unwrap_or(&0);
+    let import_count = counts.get("import").unwrap_or(&0);
 
-    let total_changes = create_count + update_count + replace_count + delete_count;
+    let total_changes = create_count + update_count + replace_count + delete_count + import_count;
 
     if total_changes == 0 {
         "No changes. Your infrastructure matches the configuration.\n".to_string()
     } else {
         format!(
-            "Plan: {} to add, {} to change, {} to destroy.\n",
+            "Plan: {}{} to add, {} to change, {} to destroy.\n",
+            format_import_count(*import_count as usize),
             create_count,
             update_count + replace_count,
             delete_count
@@ -130,6 +723,15 @@ fn format_summary(counts: &HashMap<&str, i32>) -> String {
     }
 }
 
+/// `N to import, ` when the plan imports anything, matching Terraform's summary.
+pub fn format_import_count(imports: usize) -> String {
+    if imports == 0 {
+        String::new()
+    } else {
+        format!("{} to import, ", imports)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -147,7 +749,9 @@ mod tests {
                             "ami": "ami-12345678",
                             "instance_type": "t3.micro"
                         })),
+                        ..Default::default()
                     },
+                    ..Default::default()
                 },
                 ResourceChange {
                     address: "aws_s3_bucket.data".to_string(),
@@ -161,9 +765,12 @@ mod tests {
                             "encryption": "AES256",
                             "versioning": true
                         })),
+                        ..Default::default()
                     },
+                    ..Default::default()
                 },
             ],
+            ..Default::default()
         }
     }
 
@@ -213,6 +820,7 @@ mod tests {
     fn test_empty_plan() {
         let plan = TerraformPlan {
             resource_changes: vec![],
+            ..Default::default()
         };
         let output = format_plan(&plan, false);
 
@@ -238,10 +846,12 @@ mod tests {
                 actions: vec!["create".to_string()],
                 before: None,
                 after: Some(json!({"ami": "ami-123"})),
+                ..Default::default()
             },
+            ..Default::default()
         };
 
-        let output = format_resource_change(&change, false);
+        let output = format_resource_change(&change, false, RiskLevel::Low, Annotations::default());
         assert!(output.contains("▼ + aws_instance.test will be create"));
         assert!(output.contains("ami: \"ami-123\""));
     }
@@ -254,10 +864,12 @@ mod tests {
                 actions: vec!["update".to_string()],
                 before: Some(json!({"size": "small"})),
                 after: Some(json!({"size": "large"})),
+                ..Default::default()
             },
+            ..Default::default()
         };
 
-        let output = format_resource_change(&change, false);
+        let output = format_resource_change(&change, false, RiskLevel::Low, Annotations::default());
         assert!(output.contains("▼ ~ aws_instance.test will be update"));
         assert!(output.contains("size: \"small\" => \"large\""));
     }
@@ -270,10 +882,12 @@ mod tests {
                 actions: vec!["delete".to_string()],
                 before: Some(json!({"ami": "ami-123"})),
                 after: None,
+                ..Default::default()
             },
+            ..Default::default()
         };
 
-        let output = format_resource_change(&change, false);
+        let output = format_resource_change(&change, false, RiskLevel::Low, Annotations::default());
         assert!(output.contains("▼ - aws_instance.test will be delete"));
     }
 
@@ -285,11 +899,233 @@ mod tests {
                 actions: vec!["delete".to_string(), "create".to_string()],
                 before: Some(json!({"ami": "ami-old"})),
                 after: Some(json!({"ami": "ami-new"})),
+                ..Default::default()
             },
+            ..Default::default()
         };
 
-        let output = format_resource_change(&change, false);
+        let output = format_resource_change(&change, false, RiskLevel::Low, Annotations::default());
         assert!(output.contains("▼ -/+ aws_instance.test will be replace"));
         assert!(output.contains("ami: \"ami-old\" => \"ami-new\""));
     }
+
+    #[test]
+    fn test_format_plan_highlights_high_risk() {
+        let plan = TerraformPlan {
+            resource_changes: vec![ResourceChange {
+                address: "aws_s3_bucket.logs".to_string(),
+                resource_type: "aws_s3_bucket".to_string(),
+                change: Change {
+                    actions: vec!["delete".to_string()],
+                    before: Some(json!({"bucket": "logs"})),
+                    ..Default::default()
+                },
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let output = format_plan(&plan, true);
+
+        assert!(output.starts_with("⚠ High-risk changes:\n    [CRITICAL] aws_s3_bucket.logs"));
+        assert!(output.contains("▶ - aws_s3_bucket.logs will be delete [CRITICAL]"));
+        assert!(output.contains("Risk: critical (score 15)"));
+    }
+
+    #[test]
+    fn test_format_moved_resource() {
+        let mut change = ResourceChange {
+            address: "module.new.aws_instance.web".to_string(),
+            previous_address: Some("module.old.aws_instance.web".to_string()),
+            change: Change {
+                actions: vec!["no-op".to_string()],
+                before: Some(json!({"ami": "ami-123"})),
+                after: Some(json!({"ami": "ami-123"})),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let output = format_resource_change(&change, true, RiskLevel::Low, Annotations::default());
+        assert!(output
+            .contains("▶ → module.old.aws_instance.web -> module.new.aws_instance.web has moved"));
+
+        change.change.actions = vec!["update".to_string()];
+        let output = format_resource_change(&change, true, RiskLevel::Low, Annotations::default());
+        assert!(output.contains("has moved and will be update"));
+    }
+
+    #[test]
+    fn test_format_imported_resource() {
+        let plan = TerraformPlan {
+            resource_changes: vec![ResourceChange {
+                address: "aws_instance.imported".to_string(),
+                change: Change {
+                    actions: vec!["no-op".to_string()],
+                    before: Some(json!({"ami": "ami-123"})),
+                    after: Some(json!({"ami": "ami-123"})),
+                    importing: Some(Importing {
+                        id: Some("i-0abc".to_string()),
+                    }),
+                    generated_config: Some(
+                        "resource \"aws_instance\" \"imported\" {\n  ami = \"ami-123\"\n}"
+                            .to_string(),
+                    ),
+                    ..Default::default()
+                },
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+
+        let output = format_plan(&plan, false);
+        assert!(output.contains("▼ ← aws_instance.imported will be imported (id \"i-0abc\")\n"));
+        assert!(output.contains("Plan: 1 to import, 0 to add, 0 to change, 0 to destroy."));
+        assert!(!output.contains("generated configuration"));
+
+        let options = FormatOptions {
+            generated_config: true,
+            ..Default::default()
+        };
+        let output = format_plan_with_options(&plan, &options);
+        assert!(output.contains("        # generated configuration:\n        resource \"aws_instance\" \"imported\" {\n          ami = \"ami-123\""));
+    }
+
+    #[test]
+    fn test_data_reads_rendered_separately() {
+        let mut plan = create_test_plan();
+        plan.resource_changes.push(ResourceChange {
+            address: "data.aws_ami.ubuntu".to_string(),
+            mode: "data".to_string(),
+            action_reason: Some("read_because_config_unknown".to_string()),
+            change: Change {
+                actions: vec!["read".to_string()],
+                ..Default::default()
+            },
+            ..Default::default()
+        });
+
+        let output = format_plan(&plan, true);
+        assert!(!output.contains("data.aws_ami.ubuntu will be"));
+        assert!(output.contains(
+            "Data sources to be read during apply:\n    <= data.aws_ami.ubuntu (config refers to values not yet known)\n"
+        ));
+        assert!(output.contains("Plan: 1 to add, 1 to change, 0 to destroy"));
+    }
+
+    #[test]
+    fn test_deposed_object() {
+        let plan = TerraformPlan {
+            resource_changes: vec![
+                ResourceChange {
+                    address: "aws_instance.web".to_string(),
+                    deposed: Some("abc123".to_string()),
+                    change: Change {
+                        actions: vec!["delete".to_string()],
+                        before: Some(json!({"ami": "ami-123"})),
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                },
+                ResourceChange {
+                    address: "aws_instance.web".to_string(),
+                    change: Change {
+                        actions: vec!["create".to_string()],
+                        after: Some(json!({"ami": "ami-123"})),
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                },
+            ],
+            ..Default::default()
+        };
+
+        let output = format_plan(&plan, true);
+        assert!(output.contains("▶ - aws_instance.web (deposed object abc123) will be delete"));
+        assert!(output.contains("▶ + aws_instance.web will be create"));
+        assert!(!output.contains("Probable missing moved blocks"));
+        assert!(output.contains("Plan: 1 to add, 0 to change, 1 to destroy"));
+    }
+
+    #[test]
+    fn test_format_multiline_string_change() {
+        let change = ResourceChange {
+            address: "aws_instance.test".to_string(),
+            change: Change {
+                actions: vec!["update".to_string()],
+                before: Some(json!({"user_data": "#!/bin/bash\necho old\nexit 0\n"})),
+                after: Some(json!({"user_data": "#!/bin/bash\necho new\nexit 0\n"})),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let output = format_resource_change(&change, false, RiskLevel::Low, Annotations::default());
+        assert!(output.contains(
+            "        user_data:\n              #!/bin/bash\n            - echo old\n            + echo new\n              exit 0\n"
+        ));
+    }
+
+    #[test]
+    fn test_format_json_string_change() {
+        let change = ResourceChange {
+            address: "aws_iam_policy.test".to_string(),
+            change: Change {
+                actions: vec!["update".to_string()],
+                before: Some(json!({
+                    "policy": "{\"Statement\":[{\"Effect\":\"Allow\"}]}",
+                    "tags": "{\"a\": 1, \"b\": 2}"
+                })),
+                after: Some(json!({
+                    "policy": "{\n  \"Statement\": [{\"Effect\": \"Deny\"}]\n}",
+                    "tags": "{\"b\": 2, \"a\": 1}"
+                })),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let output = format_resource_change(&change, false, RiskLevel::Low, Annotations::default());
+        assert!(output.contains(
+            "        policy (JSON):\n            Statement[0].Effect: \"Allow\" => \"Deny\"\n"
+        ));
+        assert!(output.contains("        tags: (JSON formatting only, no semantic change)\n"));
+    }
+
+    #[test]
+    fn test_unchanged_attributes_hidden() {
+        let plan = TerraformPlan {
+            resource_changes: vec![ResourceChange {
+                address: "aws_instance.web".to_string(),
+                change: Change {
+                    actions: vec!["update".to_string()],
+                    before: Some(json!({
+                        "ami": "ami-1",
+                        "instance_type": "t3.micro",
+                        "root_block_device": [{"volume_size": 8}]
+                    })),
+                    after: Some(json!({
+                        "ami": "ami-2",
+                        "instance_type": "t3.micro",
+                        "root_block_device": [{"volume_size": 8}]
+                    })),
+                    ..Default::default()
+                },
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+
+        let output = format_plan(&plan, false);
+        assert!(output.contains(
+            "        ami: \"ami-1\" => \"ami-2\"\n        # (1 unchanged attribute hidden)\n        # (1 unchanged block hidden)\n\n"
+        ));
+
+        let options = FormatOptions {
+            show_unchanged: true,
+            ..Default::default()
+        };
+        let output = format_plan_with_options(&plan, &options);
+        assert!(output.contains("        instance_type: \"t3.micro\"\n"));
+        assert!(!output.contains("hidden"));
+    }
 }
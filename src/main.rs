@@ -1,76 +1,573 @@
-use clap::Parser;
+use clap::{Args, Parser, Subcommand};
 use colored::*;
+use std::fmt::{self, Write as _};
 use std::fs;
-use std::io::{self, Read, Write};
-use terraform_plan_formatter::{Change, ResourceChange, TerraformPlan};
+use std::io::{self, BufRead, BufReader, IsTerminal, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::time::SystemTime;
+use terraform_plan_formatter::{
+    detect_probable_moves, diff_lines, diff_plans, find_config_file, format_check_counts,
+    format_checks, format_deferred_counts, format_diagnostics, format_hcl_block,
+    format_import_count, format_output_change, format_plan_diff, format_plan_markdown,
+    format_plan_with_options, format_skipped, format_state_summary, html_escape,
+    is_multiline_change, is_plan_stream, json_string_diff, parse_duration, parse_plan_stream,
+    prepare_plan, sort_changes, Annotations, ApplyProgress, ApplyStatus, Change, ChangeView,
+    CheckResult, Config, DeferredChange, Diagnostic, DiffLine, FormatOptions, IgnorePath,
+    InputVariable, JsonChange, JsonStringDiff, OutputFormat, PlanChanges, PlanStats, RenderStyle,
+    ResourceChange, ResourceProgress, RiskLevel, RiskRule, SchemaIndex, Severity, SortOrder,
+    TerraformPlan, TerraformState, Unchanged, DIFF_CONTEXT_LINES,
+};
 
 #[derive(Parser)]
-#[command(name = "tfplan")]
+#[command(name = "tfplan", version)]
 #[command(about = "Format Terraform plan output in human-readable format")]
+#[command(args_conflicts_with_subcommands = true)]
 struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    /// Arguments of the default `render` command
+    #[command(flatten)]
+    render: RenderArgs,
+
+    #[command(flatten)]
+    options: GlobalOptions,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Render a plan (the default when no command is given)
+    Render(RenderArgs),
+    /// Print the plan summary and risk score
+    Summary(InputArgs),
+    /// List high-risk changes and fail if the plan reaches a risk level
+    Check {
+        #[command(flatten)]
+        input: InputArgs,
+
+        /// Exit with status 1 when any change is at least this risky
+        #[arg(long, value_name = "LEVEL", default_value = "high")]
+        fail_on: RiskLevel,
+    },
+    /// Compare the changes of two plans, e.g. before and after a rebase
+    Diff {
+        /// Earlier plan JSON file
+        #[arg(value_name = "OLD")]
+        old: String,
+        /// Later plan JSON file
+        #[arg(value_name = "NEW")]
+        new: String,
+    },
+    /// Browse the plan interactively, expanding resources on demand
+    Tui(InputArgs),
+    /// Follow `terraform apply -json` output, showing each resource's progress
+    Apply(InputArgs),
+    /// Serve the HTML rendering over HTTP, re-reading FILE on every request
+    Serve {
+        #[command(flatten)]
+        input: InputArgs,
+
+        /// Address to listen on
+        #[arg(long, value_name = "ADDR", default_value = "127.0.0.1:8080")]
+        listen: String,
+    },
+    /// Inspect the configuration
+    Config {
+        #[command(subcommand)]
+        action: ConfigCommand,
+    },
+}
+
+#[derive(Subcommand)]
+enum ConfigCommand {
+    /// Print the effective configuration: the config file merged with flags
+    Show,
+}
+
+#[derive(Args)]
+struct InputArgs {
     /// Path to Terraform plan JSON file (use - for stdin)
     #[arg(value_name = "FILE")]
     file: Option<String>,
+}
 
-    /// Show collapsed view (only resource headers)
-    #[arg(short, long)]
-    collapsed: bool,
+#[derive(Args)]
+struct RenderArgs {
+    #[command(flatten)]
+    input: InputArgs,
+
+    /// Output format: text, html, interactive or markdown
+    #[arg(long, value_name = "FORMAT")]
+    format: Option<OutputFormat>,
+
+    /// Output as HTML with expandable sections (same as --format html)
+    #[arg(long, conflicts_with_all = ["format", "interactive"])]
+    html: bool,
 
     /// Interactive mode - press number to expand/collapse resources
-    #[arg(short, long)]
+    /// (same as --format interactive)
+    #[arg(short, long, conflicts_with = "format")]
     interactive: bool,
+}
 
-    /// Output as HTML with expandable sections
-    #[arg(long)]
-    html: bool,
+impl RenderArgs {
+    fn format(&self) -> Option<OutputFormat> {
+        if self.html {
+            Some(OutputFormat::Html)
+        } else if self.interactive {
+            Some(OutputFormat::Interactive)
+        } else {
+            self.format
+        }
+    }
+}
+
+/// Options shared by every command.
+#[derive(Args)]
+struct GlobalOptions {
+    /// Show collapsed view (only resource headers)
+    #[arg(short, long, global = true, overrides_with = "no_collapsed")]
+    collapsed: bool,
+
+    /// Turn off --collapsed set in the configuration file
+    #[arg(long, global = true, overrides_with = "collapsed")]
+    no_collapsed: bool,
+
+    /// Override risk classification as TYPE[:ACTIONS][@ATTRIBUTES]=LEVEL (repeatable)
+    #[arg(long = "risk-rule", value_name = "RULE", global = true)]
+    risk_rules: Vec<RiskRule>,
+
+    /// Resource order: risk (default: high-risk changes first, plan order otherwise),
+    /// plan, action (destroys first), address, module, type or changes
+    #[arg(long, value_name = "ORDER", global = true)]
+    sort: Option<SortOrder>,
+
+    /// Attribute layout: compact (key: old => new) or hcl (Terraform's own layout)
+    #[arg(long, value_name = "STYLE", global = true)]
+    style: Option<RenderStyle>,
+
+    /// Show counts per resource type, provider and module in place of the summary line
+    #[arg(long, global = true, overrides_with = "no_stats")]
+    stats: bool,
+
+    /// Turn off --stats set in the configuration file
+    #[arg(long, global = true, overrides_with = "stats")]
+    no_stats: bool,
+
+    /// Show the configuration Terraform generated for imported resources
+    #[arg(long, global = true, overrides_with = "no_generated_config")]
+    generated_config: bool,
+
+    /// Turn off --generated-config set in the configuration file
+    #[arg(long, global = true, overrides_with = "generated_config")]
+    no_generated_config: bool,
+
+    /// Render a state snapshot (`terraform show -json` without a plan file).
+    /// Detected automatically when the input has `values` but no `resource_changes`
+    #[arg(long, global = true)]
+    state: bool,
+
+    /// Hide cosmetic differences: null vs empty collections, tags_all mirroring
+    /// tags and reordered sets (needs --schema; without it lists are compared
+    /// in order)
+    #[arg(long, global = true, overrides_with = "no_reduce_noise")]
+    reduce_noise: bool,
+
+    /// Turn off --reduce-noise set in the configuration file
+    #[arg(long, global = true, overrides_with = "reduce_noise")]
+    no_reduce_noise: bool,
+
+    /// Ignore differences in an attribute as [TYPE:]PATH, e.g. aws_instance:user_data
+    /// (repeatable, implies --reduce-noise)
+    #[arg(long = "ignore", value_name = "PATH", global = true)]
+    ignore_paths: Vec<IgnorePath>,
+
+    /// List the attributes an update leaves unchanged instead of counting them
+    #[arg(long, global = true, overrides_with = "no_show_unchanged")]
+    show_unchanged: bool,
+
+    /// Turn off --show-unchanged set in the configuration file
+    #[arg(long, global = true, overrides_with = "show_unchanged")]
+    no_show_unchanged: bool,
+
+    /// Warn when the plan is older than this, e.g. 24h or 7d
+    #[arg(long, value_name = "DURATION", global = true, value_parser = duration_arg)]
+    max_age: Option<String>,
+
+    /// Exit with status 1 when a check block or condition fails
+    #[arg(long, global = true, overrides_with = "no_fail_on_checks")]
+    fail_on_checks: bool,
+
+    /// Turn off --fail-on-checks set in the configuration file
+    #[arg(long, global = true, overrides_with = "fail_on_checks")]
+    no_fail_on_checks: bool,
+
+    /// Show every object changed outside of Terraform, not only the drift the
+    /// planned changes depend on
+    #[arg(long, global = true, overrides_with = "no_all_drift")]
+    all_drift: bool,
+
+    /// Turn off --all-drift set in the configuration file
+    #[arg(long, global = true, overrides_with = "all_drift")]
+    no_all_drift: bool,
+
+    /// Provider schemas from `terraform providers schema -json`, to lay out
+    /// nested blocks, describe attributes and warn about deprecated ones
+    #[arg(long, value_name = "FILE", global = true)]
+    schema: Option<PathBuf>,
+
+    /// Configuration file to use instead of searching for .tfplan.toml
+    #[arg(long, value_name = "FILE", global = true)]
+    config: Option<PathBuf>,
+}
+
+/// The setting of a flag given as `--x` or `--no-x`, if either was given.
+fn flag(on: bool, off: bool) -> Option<bool> {
+    (on || off).then_some(on)
+}
+
+impl GlobalOptions {
+    /// Settings given as flags, layered over the configuration file.
+    fn overrides(&self, format: Option<OutputFormat>) -> Config {
+        Config {
+            format: format.map(|format| format.to_string()),
+            collapsed: flag(self.collapsed, self.no_collapsed),
+            sort: self.sort.map(|sort| sort.to_string()),
+            style: self.style.map(|style| style.to_string()),
+            stats: flag(self.stats, self.no_stats),
+            generated_config: flag(self.generated_config, self.no_generated_config),
+            reduce_noise: flag(self.reduce_noise, self.no_reduce_noise),
+            show_unchanged: flag(self.show_unchanged, self.no_show_unchanged),
+            max_age: self.max_age.clone(),
+            fail_on_checks: flag(self.fail_on_checks, self.no_fail_on_checks),
+            all_drift: flag(self.all_drift, self.no_all_drift),
+            schema: self.schema.as_ref().map(|path| path.display().to_string()),
+            ignore: self.ignore_paths.iter().map(ToString::to_string).collect(),
+            risk_rules: self.risk_rules.iter().map(ToString::to_string).collect(),
+        }
+    }
+}
+
+fn duration_arg(text: &str) -> Result<String, String> {
+    parse_duration(text).map(|_| text.to_string())
 }
 
-fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// `$XDG_CONFIG_HOME`, or `~/.config` when it is unset.
+fn xdg_config_home() -> Option<PathBuf> {
+    std::env::var_os("XDG_CONFIG_HOME")
+        .filter(|home| !home.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+}
+
+fn main() -> Result<ExitCode, Box<dyn std::error::Error>> {
     let cli = Cli::parse();
+    let command = cli.command.unwrap_or(Command::Render(cli.render));
+
+    let format = match &command {
+        Command::Render(args) => args.format(),
+        Command::Tui(_) => Some(OutputFormat::Interactive),
+        Command::Serve { .. } => Some(OutputFormat::Html),
+        _ => None,
+    };
+    let config_path = match &cli.options.config {
+        Some(path) => Some(path.clone()),
+        None => find_config_file(&std::env::current_dir()?, xdg_config_home().as_deref()),
+    };
+    let file_config = match &config_path {
+        Some(path) => Config::load(path)?,
+        None => Config::default(),
+    };
+    let config = file_config.merge(cli.options.overrides(format));
+    let mut options = config.format_options()?;
+    if let Some(path) = &config.schema {
+        options.schema = Some(SchemaIndex::load(path.as_ref())?);
+    }
+    let force_state = cli.options.state;
+
+    match command {
+        Command::Render(RenderArgs { input, .. }) | Command::Tui(input) => {
+            let (plan, options) = load_plan(input.file.as_deref(), force_state, &options)?;
+            match config.output_format()? {
+                OutputFormat::Html => print!("{}", render_html(&plan, &options)?),
+                OutputFormat::Interactive => interactive_format(&plan, &options)?,
+                OutputFormat::Markdown => print!("{}", format_plan_markdown(&plan, &options)),
+                OutputFormat::Text => print!("{}", format_plan_with_options(&plan, &options)),
+            }
+            return Ok(plan_status(&plan, &options));
+        }
+        Command::Summary(input) => {
+            let (plan, options) = load_plan(input.file.as_deref(), force_state, &options)?;
+            print!("{}", format_summary(&plan, &options));
+            return Ok(plan_status(&plan, &options));
+        }
+        Command::Check { input, fail_on } => {
+            let (plan, options) = load_plan(input.file.as_deref(), force_state, &options)?;
+            let (plan, _) = prepare_plan(&plan, &options);
+            let report = options.risk.assess(&plan);
+            print!(
+                "{}",
+                plan.metadata
+                    .format_header(options.max_age, SystemTime::now())
+            );
+            print!("{}", report.format_header());
+            print!("{}", format_diagnostics(&plan.reported_diagnostics()));
+            print!("{}", format_checks(&plan.checks));
+            print!("{}", PlanStats::from_plan(&plan).format_summary());
+            print!("{}", report.format_score());
+            if let Some(reason) = plan_failure(&plan, &options) {
+                eprintln!("{}", reason);
+                return Ok(ExitCode::FAILURE);
+            }
+            if let Some(level) = report.level.filter(|level| *level >= fail_on) {
+                eprintln!("Plan risk is {}, failing at {} or above.", level, fail_on);
+                return Ok(ExitCode::FAILURE);
+            }
+        }
+        Command::Diff { old, new } => {
+            let (old, _) = load_plan(Some(&old), false, &options)?;
+            let (new, _) = load_plan(Some(&new), false, &options)?;
+            print!("{}", format_plan_diff(&diff_plans(&old, &new)));
+        }
+        Command::Apply(input) => return follow_apply(input.file.as_deref()),
+        Command::Serve { input, listen } => {
+            serve(input.file.as_deref(), force_state, &options, &listen)?;
+        }
+        Command::Config {
+            action: ConfigCommand::Show,
+        } => {
+            match &config_path {
+                Some(path) => println!("# Configuration file: {}", path.display()),
+                None => println!("# No configuration file found"),
+            }
+            print!("{}", config.resolved()?.to_toml());
+        }
+    }
+
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Shows the progress of an apply as its `-json` stream comes in: redrawn in
+/// place on a terminal, one line per update otherwise. Ends with a report,
+/// failing when a resource failed or Terraform reported an error.
+fn follow_apply(file: Option<&str>) -> Result<ExitCode, Box<dyn std::error::Error>> {
+    let reader: Box<dyn BufRead> = match file {
+        Some("-") | None => Box::new(io::stdin().lock()),
+        Some(path) => Box::new(BufReader::new(fs::File::open(path)?)),
+    };
+    let live = io::stdout().is_terminal();
+    let mut progress = ApplyProgress::new();
+    let mut drawn = 0;
+    for (index, line) in reader.lines().enumerate() {
+        let updated = progress
+            .update(&line?)
+            .map_err(|e| format!("line {}: {}", index + 1, e))?;
+        let Some(updated) = updated else {
+            continue;
+        };
+        if live {
+            clear_lines(drawn);
+            for resource in &progress.resources {
+                println!("{}", progress_line(resource));
+            }
+            drawn = progress.resources.len();
+        } else if progress.resources[updated].status != ApplyStatus::Pending {
+            println!("{}", progress.resources[updated].format());
+        }
+        io::stdout().flush()?;
+    }
+    if live {
+        clear_lines(drawn);
+    } else if !progress.resources.is_empty() {
+        println!();
+    }
+    print!("{}", progress.format_report());
+    Ok(if progress.has_errors() {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    })
+}
+
+/// Moves the cursor up over the last `count` lines and erases them.
+fn clear_lines(count: usize) {
+    if count > 0 {
+        print!("\x1B[{}A\x1B[J", count);
+    }
+}
+
+fn progress_line(resource: &ResourceProgress) -> ColoredString {
+    let line = resource.format();
+    match resource.status {
+        ApplyStatus::Pending => line.dimmed(),
+        ApplyStatus::InProgress => line.yellow(),
+        ApplyStatus::Done => line.green(),
+        ApplyStatus::Failed => line.red().bold(),
+    }
+}
+
+/// Why the command should fail regardless of risk: Terraform reported
+/// errors while planning, or a check failed and `--fail-on-checks` is set.
+fn plan_failure(plan: &TerraformPlan, options: &FormatOptions) -> Option<String> {
+    if plan.has_errors() {
+        return Some("Terraform reported errors while planning; the plan is incomplete.".into());
+    }
+    if options.fail_on_checks && plan.has_failed_checks() {
+        return Some(format!("Checks: {}.", format_check_counts(&plan.checks)));
+    }
+    None
+}
+
+fn plan_status(plan: &TerraformPlan, options: &FormatOptions) -> ExitCode {
+    match plan_failure(plan, options) {
+        Some(reason) => {
+            eprintln!("{}", reason);
+            ExitCode::FAILURE
+        }
+        None => ExitCode::SUCCESS,
+    }
+}
 
-    let content = match cli.file.as_deref() {
+fn read_input(file: Option<&str>) -> io::Result<String> {
+    match file {
         Some("-") | None => {
             let mut buffer = String::new();
             io::stdin().read_to_string(&mut buffer)?;
-            buffer
+            Ok(buffer)
         }
-        Some(path) => fs::read_to_string(path)?,
+        Some(path) => fs::read_to_string(path),
+    }
+}
+
+/// Parses a plan, or a state snapshot when forced or when the document has
+/// `values` but no `resource_changes`, and marks the options accordingly.
+fn parse_plan(
+    content: &str,
+    force_state: bool,
+    options: &FormatOptions,
+) -> Result<(TerraformPlan, FormatOptions), Box<dyn std::error::Error>> {
+    if is_plan_stream(content) {
+        return Ok((parse_plan_stream(content)?, options.clone()));
+    }
+    let document: serde_json::Value = serde_json::from_str(content)?;
+    let state = force_state
+        || (document.get("resource_changes").is_none() && document.get("values").is_some());
+    let plan: TerraformPlan = if state {
+        serde_json::from_value::<TerraformState>(document)?.to_plan()
+    } else {
+        serde_json::from_value(document)?
+    };
+    let options = FormatOptions {
+        state,
+        ..options.clone()
     };
+    Ok((plan, options))
+}
 
-    let plan: TerraformPlan = serde_json::from_str(&content)?;
+fn load_plan(
+    file: Option<&str>,
+    force_state: bool,
+    options: &FormatOptions,
+) -> Result<(TerraformPlan, FormatOptions), Box<dyn std::error::Error>> {
+    parse_plan(&read_input(file)?, force_state, options)
+}
 
-    if cli.html {
-        html_format(&plan);
-    } else if cli.interactive {
-        interactive_format(&plan)?;
-    } else {
-        print!(
-            "{}",
-            terraform_plan_formatter::format_plan(&plan, cli.collapsed)
-        );
+/// The high-risk changes, the summary line or, with `--stats`, the breakdown
+/// tables, and the risk score.
+fn format_summary(plan: &TerraformPlan, options: &FormatOptions) -> String {
+    if options.state {
+        return format_state_summary(plan);
     }
+    let (plan, noise) = prepare_plan(plan, options);
+    let stats = PlanStats::from_plan(&plan);
+    let report = options.risk.assess(&plan);
+    let mut output = plan
+        .metadata
+        .format_header(options.max_age, SystemTime::now());
+    output.push_str(&report.format_header());
+    output.push_str(&if options.stats {
+        stats.format_text()
+    } else {
+        stats.format_summary()
+    });
+    output.push_str(&noise.format());
+    output.push_str(&report.format_score());
+    output
+}
 
+/// Serves the HTML rendering until interrupted. A file is re-read on every
+/// request, so reloading the page picks up a new plan; stdin is read once.
+/// A connection that fails is reported and the next one is served.
+fn serve(
+    file: Option<&str>,
+    force_state: bool,
+    options: &FormatOptions,
+    listen: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let stdin = match file {
+        Some("-") | None => Some(read_input(None)?),
+        Some(_) => None,
+    };
+    let listener = TcpListener::bind(listen)?;
+    eprintln!("Serving on http://{}", listener.local_addr()?);
+
+    for stream in listener.incoming() {
+        let served = stream.and_then(|stream| {
+            respond(stream, || {
+                match &stdin {
+                    Some(content) => Ok(content.clone()),
+                    None => read_input(file).map_err(Into::into),
+                }
+                .and_then(|content| parse_plan(&content, force_state, options))
+                .and_then(|(plan, options)| Ok(render_html(&plan, &options)?))
+            })
+        });
+        if let Err(e) = served {
+            eprintln!("Error serving a request: {}", e);
+        }
+    }
     Ok(())
 }
 
-fn print_changes(change: &Change) {
+/// Reads one request from `stream` and answers it with the page `render`
+/// makes, or its error.
+fn respond(
+    mut stream: TcpStream,
+    render: impl FnOnce() -> Result<String, Box<dyn std::error::Error>>,
+) -> io::Result<()> {
+    let mut request = BufReader::new(&stream);
+    let mut line = String::new();
+    while request.read_line(&mut line)? > 2 {
+        line.clear();
+    }
+
+    let (status, body) = match render() {
+        Ok(html) => ("200 OK", html),
+        Err(e) => ("500 Internal Server Error", html_escape(&e.to_string())),
+    };
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: text/html; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    )
+}
+
+fn print_changes(change: &Change, annotations: Annotations) {
     if let (Some(before), Some(after)) = (&change.before, &change.after) {
         if let (Some(before_obj), Some(after_obj)) = (before.as_object(), after.as_object()) {
             for (key, after_val) in after_obj {
                 if let Some(before_val) = before_obj.get(key) {
                     if before_val != after_val {
-                        println!(
-                            "        {}: {} {} {}",
-                            key.bright_white(),
-                            format_value(before_val).bright_red(),
-                            "=>".bright_black(),
-                            format_value(after_val).bright_green()
-                        );
+                        print_attribute_change(key, before_val, after_val, annotations);
                     }
                 } else {
                     println!(
                         "        {}: {}",
-                        key.bright_white(),
+                        tui_key(key, annotations),
                         format_value(after_val).bright_green()
                     );
                 }
@@ -79,7 +576,7 @@ fn print_changes(change: &Change) {
                 if !after_obj.contains_key(key) {
                     println!(
                         "        {}: {} {} {}",
-                        key.bright_white(),
+                        tui_key(key, annotations),
                         format_value(before_val).bright_red(),
                         "=>".bright_black(),
                         "null".bright_red()
@@ -90,13 +587,103 @@ fn print_changes(change: &Change) {
     }
 }
 
-fn print_create_attributes(change: &Change) {
+fn print_unchanged(change: &Change, expand: bool, annotations: Annotations) {
+    let unchanged = Unchanged::from_change(change);
+    if expand {
+        for (key, value) in &unchanged.attributes {
+            println!(
+                "        {}: {}",
+                tui_key(key, annotations),
+                format_value(value).bright_black()
+            );
+        }
+    } else {
+        for line in unchanged.format_counts() {
+            println!("        {}", line.bright_black());
+        }
+    }
+}
+
+fn print_attribute_change(
+    key: &str,
+    before: &serde_json::Value,
+    after: &serde_json::Value,
+    annotations: Annotations,
+) {
+    if let Some(json_diff) = json_string_diff(before, after) {
+        print_json_string_diff(key, &json_diff, annotations);
+    } else if is_multiline_change(before, after) {
+        println!("        {}:", tui_key(key, annotations));
+        print_multiline_diff(before, after);
+    } else {
+        println!(
+            "        {}: {} {} {}",
+            tui_key(key, annotations),
+            format_value(before).bright_red(),
+            "=>".bright_black(),
+            format_value(after).bright_green()
+        );
+    }
+}
+
+fn print_json_string_diff(key: &str, diff: &JsonStringDiff, annotations: Annotations) {
+    match diff {
+        JsonStringDiff::Cosmetic => println!(
+            "        {}: {}",
+            tui_key(key, annotations),
+            "(JSON formatting only, no semantic change)".bright_black()
+        ),
+        JsonStringDiff::Changes(changes) => {
+            println!(
+                "        {} {}",
+                tui_key(key, annotations),
+                "(JSON):".bright_black()
+            );
+            for change in changes {
+                let line = change.format();
+                let line = match change {
+                    JsonChange::Changed { .. } => line.bright_yellow(),
+                    JsonChange::Added { .. } => line.bright_green(),
+                    JsonChange::Removed { .. } => line.bright_red(),
+                };
+                println!("            {}", line);
+            }
+        }
+    }
+}
+
+fn multiline_diff(before: &serde_json::Value, after: &serde_json::Value) -> Vec<DiffLine> {
+    diff_lines(
+        before.as_str().unwrap_or_default(),
+        after.as_str().unwrap_or_default(),
+        DIFF_CONTEXT_LINES,
+    )
+}
+
+fn print_multiline_diff(before: &serde_json::Value, after: &serde_json::Value) {
+    for line in multiline_diff(before, after) {
+        match line {
+            DiffLine::Context(text) => println!("              {}", text),
+            DiffLine::Removed(text) => {
+                println!("            {}", format!("- {}", text).bright_red())
+            }
+            DiffLine::Added(text) => {
+                println!("            {}", format!("+ {}", text).bright_green())
+            }
+            DiffLine::Skipped(count) => {
+                println!("            {}", format_skipped(count).bright_black())
+            }
+        }
+    }
+}
+
+fn print_create_attributes(change: &Change, annotations: Annotations) {
     if let Some(after) = &change.after {
         if let Some(after_obj) = after.as_object() {
             for (key, val) in after_obj {
                 println!(
                     "        {}: {}",
-                    key.bright_white(),
+                    tui_key(key, annotations),
                     format_value(val).bright_green()
                 );
             }
@@ -104,20 +691,78 @@ fn print_create_attributes(change: &Change) {
     }
 }
 
-fn interactive_format(plan: &TerraformPlan) -> Result<(), Box<dyn std::error::Error>> {
+fn interactive_format(
+    plan: &TerraformPlan,
+    options: &FormatOptions,
+) -> Result<(), Box<dyn std::error::Error>> {
     let mut expanded: std::collections::HashSet<usize> = std::collections::HashSet::new();
+    let mut show_unchanged = options.show_unchanged;
+    let mut show_variables = false;
+    let variables = plan.input_variables();
+    let (filtered, noise) = prepare_plan(plan, options);
+    let plan = &filtered;
+    let report = options.risk.assess(plan);
+    let PlanChanges { changes, reads, .. } = PlanChanges::new(plan, options);
+    let shown_drift = plan.drift(options.all_drift);
 
     loop {
         print!("\x1B[2J\x1B[1;1H"); // Clear screen
 
-        println!("Interactive Plan (Enter number to toggle, 'a' for all, 'c' to collapse all, 'q' to quit):");
+        let title = if options.state {
+            "Interactive State"
+        } else {
+            "Interactive Plan"
+        };
+        println!(
+            "{} (Enter number to toggle, 'a' for all, 'c' to collapse all, 'u' for unchanged attributes, 'v' for variables, 'q' to quit):",
+            title
+        );
         println!();
+        print_interactive_metadata(plan, options.max_age);
+        print_interactive_variables(&variables, show_variables);
+
+        if !report.high_risk.is_empty() {
+            println!("{}", "⚠ High-risk changes:".bright_red().bold());
+            for (address, level) in &report.high_risk {
+                println!("    {} {}", risk_badge(*level), address.bold());
+            }
+            println!();
+        }
+        print_interactive_drift(&shown_drift);
 
-        for (i, change) in plan.resource_changes.iter().enumerate() {
+        for (i, view) in changes.iter().enumerate() {
             let is_expanded = expanded.contains(&i);
-            print_interactive_resource(i, change, is_expanded);
+            if options.state {
+                print_interactive_state_resource(i, view.change, is_expanded, view.annotations());
+            } else {
+                print_interactive_resource(
+                    i,
+                    view.change,
+                    is_expanded,
+                    show_unchanged,
+                    options,
+                    view.risk,
+                    view.annotations(),
+                );
+            }
         }
 
+        if options.state {
+            print!("{}", format_state_summary(plan));
+        }
+
+        print_interactive_data_reads(&reads);
+        print_interactive_deferred(&plan.deferred_changes);
+        print_interactive_output_changes(plan);
+        print_interactive_probable_moves(plan);
+        print_interactive_diagnostics(&plan.reported_diagnostics());
+        print_interactive_checks(&plan.checks);
+
+        if let Some(level) = report.level {
+            println!("Risk: {} (score {})", level, report.score);
+        }
+        print!("{}", noise.format().bright_black());
+
         print!("\nCommand: ");
         io::stdout().flush()?;
 
@@ -127,14 +772,16 @@ fn interactive_format(plan: &TerraformPlan) -> Result<(), Box<dyn std::error::Er
         match input.trim() {
             "q" => break,
             "a" => {
-                for i in 0..plan.resource_changes.len() {
+                for i in 0..changes.len() {
                     expanded.insert(i);
                 }
             }
             "c" => expanded.clear(),
+            "u" => show_unchanged = !show_unchanged,
+            "v" => show_variables = !show_variables,
             n => {
                 if let Ok(idx) = n.parse::<usize>() {
-                    if idx < plan.resource_changes.len() {
+                    if idx < changes.len() {
                         if expanded.contains(&idx) {
                             expanded.remove(&idx);
                         } else {
@@ -148,7 +795,212 @@ fn interactive_format(plan: &TerraformPlan) -> Result<(), Box<dyn std::error::Er
     Ok(())
 }
 
-fn print_interactive_resource(index: usize, change: &ResourceChange, is_expanded: bool) {
+fn print_interactive_data_reads(reads: &[&ResourceChange]) {
+    if reads.is_empty() {
+        return;
+    }
+    println!("{}", "Data sources to be read during apply:".dimmed());
+    for change in reads {
+        let reason = change
+            .read_reason()
+            .map(|reason| format!(" ({})", reason))
+            .unwrap_or_default();
+        println!(
+            "    {}",
+            format!("<= {}{}", change.address, reason).dimmed()
+        );
+    }
+    println!();
+}
+
+fn print_interactive_metadata(plan: &TerraformPlan, max_age: Option<u64>) {
+    let now = SystemTime::now();
+    let summary = plan.metadata.format_summary(now);
+    let warnings = plan.metadata.warnings(max_age, now);
+    if summary.is_empty() && warnings.is_empty() {
+        return;
+    }
+    if !summary.is_empty() {
+        println!("{}", summary.dimmed());
+    }
+    for warning in warnings {
+        println!("{}", format!("⚠ {}", warning).bright_red().bold());
+    }
+    println!();
+}
+
+fn print_interactive_variables(variables: &[InputVariable], expanded: bool) {
+    if variables.is_empty() {
+        return;
+    }
+    let indicator = if expanded { "▼" } else { "▶" };
+    println!(
+        "{} {}",
+        indicator,
+        format!("Input variables ({})", variables.len()).bold()
+    );
+    if expanded {
+        for variable in variables {
+            let value = variable.display_value();
+            let value = if variable.sensitive {
+                value.bright_black()
+            } else {
+                value.cyan()
+            };
+            println!("    {} = {}", variable.name, value);
+        }
+    }
+    println!();
+}
+
+fn print_interactive_drift(drift: &[ResourceChange]) {
+    if drift.is_empty() {
+        return;
+    }
+    println!(
+        "{}",
+        "Objects changed outside of Terraform:"
+            .bright_magenta()
+            .bold()
+    );
+    for change in drift {
+        println!("    {}", change.format_drift());
+    }
+    println!();
+}
+
+fn print_interactive_output_changes(plan: &TerraformPlan) {
+    let lines: Vec<String> = plan
+        .output_changes
+        .iter()
+        .filter_map(|(name, change)| format_output_change(name, change))
+        .collect();
+    if lines.is_empty() {
+        return;
+    }
+    println!("{}", "Changes to Outputs:".bold());
+    for line in lines {
+        println!("    {}", line);
+    }
+    println!();
+}
+
+fn print_interactive_diagnostics(diagnostics: &[Diagnostic]) {
+    for diagnostic in diagnostics {
+        let label = format!("{}:", diagnostic.severity.label());
+        let label = match diagnostic.severity {
+            Severity::Error => label.bright_red().bold(),
+            Severity::Warning => label.bright_yellow().bold(),
+        };
+        println!("{} {}", label, diagnostic.summary.bold());
+        println!();
+        if let Some(address) = &diagnostic.address {
+            println!("  with {},", address);
+        }
+        if let Some(location) = diagnostic.location() {
+            println!("  {}", location);
+        }
+        if let Some(snippet) = &diagnostic.snippet {
+            for line in snippet.lines() {
+                println!(
+                    "  {}{}{}{}",
+                    format!("{}: ", line.number).dimmed(),
+                    line.before,
+                    line.highlight.underline().bold(),
+                    line.after
+                );
+            }
+            for value in &snippet.values {
+                println!(
+                    "    {} {} {}",
+                    "│".dimmed(),
+                    value.traversal.bold(),
+                    value.statement
+                );
+            }
+        }
+        if diagnostic.address.is_some() || diagnostic.range.is_some() {
+            println!();
+        }
+        if !diagnostic.detail.is_empty() {
+            println!("{}", diagnostic.detail);
+            println!();
+        }
+    }
+}
+
+fn print_interactive_checks(checks: &[CheckResult]) {
+    if checks.is_empty() {
+        return;
+    }
+    println!("{} {}", "Checks:".bold(), format_check_counts(checks));
+    for check in checks.iter().filter(|check| check.status.is_failure()) {
+        println!(
+            "    {}",
+            format!("✗ {} {}", check.describe(), check.status.label())
+                .bright_red()
+                .bold()
+        );
+        for message in check.messages() {
+            println!("        {}", message.bright_red());
+        }
+    }
+    println!();
+}
+
+fn print_interactive_deferred(deferred: &[DeferredChange]) {
+    if deferred.is_empty() {
+        return;
+    }
+    println!(
+        "{}",
+        format!(
+            "Deferred changes: {}, to be planned after this plan is applied:",
+            format_deferred_counts(deferred)
+        )
+        .bright_yellow()
+        .bold()
+    );
+    for change in deferred {
+        println!("    {}", change.format());
+    }
+    println!();
+}
+
+fn print_interactive_probable_moves(plan: &TerraformPlan) {
+    let moves = detect_probable_moves(plan);
+    if moves.is_empty() {
+        return;
+    }
+    println!(
+        "{}",
+        "Probable missing moved blocks:".bright_yellow().bold()
+    );
+    for probable in &moves {
+        println!("    {}", probable.format());
+    }
+    println!();
+}
+
+fn risk_badge(level: RiskLevel) -> ColoredString {
+    let label = format!("[{}]", level.as_str().to_uppercase());
+    match level {
+        RiskLevel::Critical => label.on_red().white().bold(),
+        RiskLevel::High => label.bright_red().bold(),
+        RiskLevel::Medium => label.bright_yellow(),
+        RiskLevel::Low => label.bright_black(),
+    }
+}
+
+fn print_interactive_resource(
+    index: usize,
+    change: &ResourceChange,
+    is_expanded: bool,
+    show_unchanged: bool,
+    options: &FormatOptions,
+    risk: RiskLevel,
+    annotations: Annotations,
+) {
     let (action, color) = match change.change.actions.as_slice() {
         [a] if a == "create" => ("created", "green"),
         [a] if a == "update" => ("changed", "yellow"),
@@ -162,36 +1014,128 @@ fn print_interactive_resource(index: usize, change: &ResourceChange, is_expanded
         "changed" => "~".bright_yellow().bold(),
         "destroyed" => "-".bright_red().bold(),
         "replaced" => "-/+".bright_yellow().bold(),
+        _ if change.is_importing() => "←".bright_cyan().bold(),
+        _ if change.is_moved() => "→".bright_cyan().bold(),
         _ => "?".normal(),
     };
 
     let indicator = if is_expanded { "▼" } else { "▶" };
+    let description = change.describe(action);
+    let description = match description.strip_suffix(action) {
+        Some(prefix) => format!("{}{}", prefix, action.color(color)),
+        None => description.bright_cyan().to_string(),
+    };
 
-    println!(
-        "{} {} {} {} will be {}",
+    print!(
+        "{} {} {} {} {}",
         format!("[{}]", index).bright_cyan(),
         indicator.bright_black(),
         symbol,
-        change.address.bold(),
-        action.color(color)
+        change.display_address().bold(),
+        description
     );
+    if risk.is_high() {
+        print!(" {}", risk_badge(risk));
+    }
+    if let Some(drift) = annotations.drift {
+        print!(" {}", format!("({})", drift.drift_note()).bright_magenta());
+    }
+    println!();
 
-    if is_expanded {
+    if is_expanded && options.style == RenderStyle::Hcl {
+        print_hcl_block(change, show_unchanged, annotations);
+    } else if is_expanded {
         match action {
-            "changed" | "replaced" => print_changes(&change.change),
-            "created" => print_create_attributes(&change.change),
+            "changed" | "replaced" => {
+                print_changes(&change.change, annotations);
+                print_unchanged(&change.change, show_unchanged, annotations);
+            }
+            "created" => print_create_attributes(&change.change, annotations),
             _ => {}
         }
     }
+    if is_expanded && options.generated_config {
+        print_generated_config(&change.change);
+    }
     println!();
 }
 
-fn html_format(plan: &TerraformPlan) {
+fn print_hcl_block(change: &ResourceChange, show_unchanged: bool, annotations: Annotations) {
+    for line in format_hcl_block(change, show_unchanged, annotations).lines() {
+        let line = match hcl_marker(line) {
+            "+" => line.bright_green(),
+            "-" => line.bright_red(),
+            "~" | "-/+" => line.bright_yellow(),
+            "#" => line.bright_black(),
+            _ => line.normal(),
+        };
+        println!("{}", line);
+    }
+}
+
+/// The `+`/`-`/`~` marker, or `#` for comments, leading a line of
+/// [`format_hcl_block`] output.
+fn hcl_marker(line: &str) -> &str {
+    line.split_whitespace().next().unwrap_or_default()
+}
+
+fn print_interactive_state_resource(
+    index: usize,
+    change: &ResourceChange,
+    is_expanded: bool,
+    annotations: Annotations,
+) {
+    let indicator = if is_expanded { "▼" } else { "▶" };
+
     println!(
+        "{} {} {}",
+        format!("[{}]", index).bright_cyan(),
+        indicator.bright_black(),
+        change.address.bold()
+    );
+
+    if is_expanded {
+        print_create_attributes(&change.change, annotations);
+    }
+    println!();
+}
+
+/// An attribute name, followed by the references that set it and a note on
+/// computed or deprecated attributes.
+fn tui_key(key: &str, annotations: Annotations) -> String {
+    let mut output = key.bright_white().to_string();
+    if let Some(source) = annotations.source(key) {
+        output.push_str(&format!(" {}", format!("<- {}", source).bright_blue()));
+    }
+    if let Some(note) = annotations.note(key) {
+        output.push_str(&format!(" {}", format!("({})", note).bright_black()));
+    }
+    output
+}
+
+fn print_generated_config(change: &Change) {
+    if let Some(config) = &change.generated_config {
+        println!("        {}", "# generated configuration:".bright_black());
+        for line in config.lines() {
+            println!("        {}", line.bright_cyan());
+        }
+    }
+}
+
+fn render_html(plan: &TerraformPlan, options: &FormatOptions) -> Result<String, fmt::Error> {
+    let mut html = String::new();
+    let out = &mut html;
+    let title = if options.state {
+        "Terraform State"
+    } else {
+        "Terraform Plan"
+    };
+    writeln!(
+        out,
         r#"<!DOCTYPE html>
 <html>
 <head>
-    <title>Terraform Plan</title>
+    <title>{title}</title>
     <style>
         body {{ font-family: 'Monaco', 'Menlo', monospace; background: #1e1e1e; color: #d4d4d4; padding: 20px; }}
         .resource {{ margin: 10px 0; }}
@@ -207,55 +1151,141 @@ fn html_format(plan: &TerraformPlan) {
         .value-old {{ color: #f44747; }}
         .value-new {{ color: #4ec9b0; }}
         .arrow {{ color: #808080; }}
+        .value-changed {{ color: #dcdcaa; }}
+        .cosmetic {{ opacity: 0.6; }}
+        .unchanged summary {{ color: #808080; cursor: pointer; }}
         .summary {{ margin-top: 20px; padding: 15px; background: #2d2d30; border-radius: 4px; border-top: 3px solid #007acc; }}
         .expand-icon {{ display: inline-block; width: 12px; transition: transform 0.2s; }}
         .expanded .expand-icon {{ transform: rotate(90deg); }}
+        .stats {{ margin-top: 20px; padding: 15px; background: #2d2d30; border-radius: 4px; }}
+        .stats table {{ border-collapse: collapse; margin: 10px 0 20px; }}
+        .stats th, .stats td {{ padding: 4px 12px; border-bottom: 1px solid #3e3e42; }}
+        .stats th {{ text-align: left; color: #9cdcfe; }}
+        .stats td.count {{ text-align: right; }}
+        .move {{ color: #569cd6; }}
+        .import {{ color: #569cd6; }}
+        .line-diff {{ margin: 4px 0 4px 20px; padding: 8px; background: #1e1e1e; border-radius: 4px; }}
+        .hcl {{ margin: 0; }}
+        .generated-config {{ margin: 8px 0; padding: 8px; background: #1e1e1e; border-radius: 4px; color: #9cdcfe; }}
+        .data-reads {{ margin-top: 20px; padding: 15px; background: #252526; border-radius: 4px; color: #808080; opacity: 0.8; }}
+        .metadata {{ color: #808080; }}
+        .variables {{ margin: 10px 0; padding: 10px 15px; background: #252526; border-radius: 4px; }}
+        .variables summary {{ cursor: pointer; font-weight: bold; }}
+        .variables .value {{ color: #ce9178; }}
+        .variables .sensitive {{ color: #808080; font-style: italic; }}
+        .plan-warning {{ margin: 10px 0; padding: 10px 15px; background: #5a1d1d; color: #f48771; border-radius: 4px; font-weight: bold; }}
+        .expression-source {{ color: #569cd6; font-size: 0.85em; margin-left: 4px; }}
+        .schema-note {{ color: #808080; font-size: 0.85em; font-style: italic; margin-left: 4px; }}
+        .key[title] {{ cursor: help; text-decoration: underline dotted #808080; }}
+        .drift-note {{ color: #c586c0; font-size: 0.9em; }}
+        .drift {{ margin: 10px 0 20px; padding: 15px; background: #2d2d30; border-radius: 4px; border-left: 3px solid #c586c0; }}
+        .outputs {{ margin-top: 20px; padding: 15px; background: #252526; border-radius: 4px; }}
+        .diagnostic {{ margin-top: 20px; padding: 15px; background: #2d2d30; border-radius: 4px; }}
+        .diagnostic-error {{ border-left: 3px solid #f44747; }}
+        .diagnostic-warning {{ border-left: 3px solid #dcdcaa; }}
+        .diagnostic .snippet {{ background: #1e1e1e; padding: 10px; }}
+        .diagnostic mark {{ background: none; color: #f48771; text-decoration: underline; }}
+        .diagnostic .line-number {{ color: #808080; }}
+        .checks {{ margin-top: 20px; padding: 15px; background: #252526; border-radius: 4px; }}
+        .check-failed {{ color: #f48771; }}
+        .check-message {{ margin-left: 20px; color: #f48771; font-weight: bold; }}
+        .deferred {{ margin-top: 20px; padding: 15px; background: #2d2d30; border-radius: 4px; border-left: 3px solid #dcdcaa; }}
+        .probable-moves {{ margin-top: 20px; padding: 15px; background: #2d2d30; border-radius: 4px; border-left: 3px solid #dcdcaa; }}
+        .risk-summary {{ margin: 10px 0 20px; padding: 15px; background: #3a1d1d; border-radius: 4px; border-left: 3px solid #f44747; }}
+        .risk-badge {{ margin-left: 8px; padding: 1px 6px; border-radius: 3px; font-size: 0.85em; }}
+        .risk-high {{ background: #5a1d1d; color: #f48771; }}
+        .risk-critical {{ background: #f44747; color: #ffffff; }}
     </style>
 </head>
 <body>
-    <h1>Terraform Plan</h1>
+    <h1>{title}</h1>
 "#
-    );
-
-    let mut create_count = 0;
-    let mut update_count = 0;
-    let mut delete_count = 0;
-    let mut replace_count = 0;
+    )?;
 
-    for (i, change) in plan.resource_changes.iter().enumerate() {
-        match change.change.actions.as_slice() {
-            [action] if action == "create" => {
-                create_count += 1;
-                print_html_resource(i, change, "create", "create");
-            }
-            [action] if action == "update" => {
-                update_count += 1;
-                print_html_resource(i, change, "update", "update");
-            }
-            [action] if action == "delete" => {
-                delete_count += 1;
-                print_html_resource(i, change, "destroy", "destroy");
-            }
-            [action1, action2] if action1 == "delete" && action2 == "create" => {
-                replace_count += 1;
-                print_html_resource(i, change, "replace", "replace");
-            }
-            _ => {}
+    write_html_metadata(out, plan, options)?;
+    write_html_variables(out, &plan.input_variables(), options)?;
+    let (filtered, noise) = prepare_plan(plan, options);
+    let plan = &filtered;
+    let report = options.risk.assess(plan);
+    if !report.high_risk.is_empty() {
+        writeln!(out, r#"    <div class="risk-summary">"#)?;
+        writeln!(out, "        <h3>⚠ High-risk changes</h3>")?;
+        for (address, level) in &report.high_risk {
+            writeln!(
+                out,
+                r#"        <div><span class="risk-badge risk-{}">{}</span> <strong>{}</strong></div>"#,
+                level,
+                level.as_str().to_uppercase(),
+                html_escape(address)
+            )?;
         }
+        writeln!(out, "    </div>")?;
     }
+    write_html_drift(out, &plan.drift(options.all_drift))?;
 
-    println!(
-        r#"    <div class="summary">
+    let walk = PlanChanges::new(plan, options);
+    for (i, view) in walk.changes.iter().enumerate() {
+        let action = match view.action {
+            "create" | "update" | "replace" => view.action,
+            "delete" => "destroy",
+            _ if view.change.is_importing() => "import",
+            _ if view.change.is_moved() => "move",
+            _ if options.state => "state",
+            _ => continue,
+        };
+        write_html_resource(out, i, view, action, options)?;
+    }
+    write_html_diagnostics(out, &plan.reported_diagnostics())?;
+    write_html_checks(out, &plan.checks)?;
+
+    if options.state {
+        writeln!(
+            out,
+            r#"    <div class="summary">
+        <h3>State Summary</h3>
+        <p>{}</p>
+    </div>"#,
+            html_escape(format_state_summary(plan).trim_end())
+        )?;
+    } else {
+        let count = |action| walk.counts.get(action).copied().unwrap_or(0);
+        writeln!(
+            out,
+            r#"    <div class="summary">
         <h3>Plan Summary</h3>
-        <p><span style="color: #4ec9b0;">{}</span> to add, <span style="color: #dcdcaa;">{}</span> to change, <span style="color: #f44747;">{}</span> to destroy.</p>
+        <p>{}<span style="color: #4ec9b0;">{}</span> to add, <span style="color: #dcdcaa;">{}</span> to change, <span style="color: #f44747;">{}</span> to destroy.</p>
+        <p>Risk: {} (score {})</p>
     </div>
 "#,
-        create_count,
-        update_count + replace_count,
-        delete_count
-    );
+            format_import_count(count("import") as usize),
+            count("create"),
+            count("update") + count("replace"),
+            count("delete"),
+            report.level.map_or("none", |level| level.as_str()),
+            report.score
+        )?;
+    }
 
-    println!(
+    let noise = noise.format();
+    if !noise.is_empty() {
+        writeln!(
+            out,
+            r#"    <p class="arrow">{}</p>"#,
+            html_escape(noise.trim_end())
+        )?;
+    }
+
+    write_html_data_reads(out, plan, options)?;
+    write_html_deferred(out, &plan.deferred_changes)?;
+    write_html_output_changes(out, plan)?;
+    write_html_probable_moves(out, plan)?;
+
+    if options.stats {
+        write_html_stats(out, &PlanStats::from_plan(plan))?;
+    }
+
+    writeln!(
+        out,
         r#"    <script>
         function toggleResource(id) {{
             const details = document.getElementById('details-' + id);
@@ -271,10 +1301,306 @@ fn html_format(plan: &TerraformPlan) {
     </script>
 </body>
 </html>"#
-    );
+    )?;
+    Ok(html)
+}
+
+fn write_html_data_reads(
+    out: &mut String,
+    plan: &TerraformPlan,
+    options: &FormatOptions,
+) -> fmt::Result {
+    let reads: Vec<&ResourceChange> = sort_changes(&plan.resource_changes, options.sort)
+        .into_iter()
+        .filter(|change| change.is_data_read())
+        .collect();
+    if reads.is_empty() {
+        return Ok(());
+    }
+    writeln!(out, r#"    <div class="data-reads">"#)?;
+    writeln!(out, "        <h3>Data sources to be read during apply</h3>")?;
+    for change in reads {
+        let reason = change
+            .read_reason()
+            .map(|reason| format!(" ({})", reason))
+            .unwrap_or_default();
+        writeln!(
+            out,
+            "        <div>&lt;= <strong>{}</strong>{}</div>",
+            html_escape(&change.address),
+            html_escape(&reason)
+        )?;
+    }
+    writeln!(out, "    </div>")?;
+    Ok(())
+}
+
+fn write_html_metadata(
+    out: &mut String,
+    plan: &TerraformPlan,
+    options: &FormatOptions,
+) -> fmt::Result {
+    let now = SystemTime::now();
+    let summary = plan.metadata.format_summary(now);
+    if !summary.is_empty() {
+        writeln!(
+            out,
+            r#"    <p class="metadata">{}</p>"#,
+            html_escape(&summary)
+        )?;
+    }
+    for warning in plan.metadata.warnings(options.max_age, now) {
+        writeln!(
+            out,
+            r#"    <div class="plan-warning">⚠ {}</div>"#,
+            html_escape(&warning)
+        )?;
+    }
+    Ok(())
 }
 
-fn print_html_resource(index: usize, change: &ResourceChange, action: &str, css_class: &str) {
+fn write_html_variables(
+    out: &mut String,
+    variables: &[InputVariable],
+    options: &FormatOptions,
+) -> fmt::Result {
+    if variables.is_empty() {
+        return Ok(());
+    }
+    writeln!(
+        out,
+        r#"    <details class="variables"{}>"#,
+        if options.collapsed { "" } else { " open" }
+    )?;
+    writeln!(
+        out,
+        "        <summary>Input variables ({})</summary>",
+        variables.len()
+    )?;
+    for variable in variables {
+        writeln!(
+            out,
+            r#"        <div>{} = <span class="{}">{}</span></div>"#,
+            html_escape(&variable.name),
+            if variable.sensitive {
+                "sensitive"
+            } else {
+                "value"
+            },
+            html_escape(&variable.display_value())
+        )?;
+    }
+    writeln!(out, "    </details>")?;
+    Ok(())
+}
+
+fn write_html_drift(out: &mut String, drift: &[ResourceChange]) -> fmt::Result {
+    if drift.is_empty() {
+        return Ok(());
+    }
+    writeln!(out, r#"    <div class="drift">"#)?;
+    writeln!(out, "        <h3>Objects changed outside of Terraform</h3>")?;
+    for change in drift {
+        writeln!(
+            out,
+            "        <div>{}</div>",
+            html_escape(&change.format_drift())
+        )?;
+    }
+    writeln!(out, "    </div>")?;
+    Ok(())
+}
+
+fn write_html_output_changes(out: &mut String, plan: &TerraformPlan) -> fmt::Result {
+    let lines: Vec<String> = plan
+        .output_changes
+        .iter()
+        .filter_map(|(name, change)| format_output_change(name, change))
+        .collect();
+    if lines.is_empty() {
+        return Ok(());
+    }
+    writeln!(out, r#"    <div class="outputs">"#)?;
+    writeln!(out, "        <h3>Changes to Outputs</h3>")?;
+    for line in lines {
+        writeln!(out, "        <div>{}</div>", html_escape(&line))?;
+    }
+    writeln!(out, "    </div>")?;
+    Ok(())
+}
+
+fn write_html_diagnostics(out: &mut String, diagnostics: &[Diagnostic]) -> fmt::Result {
+    for diagnostic in diagnostics {
+        writeln!(
+            out,
+            r#"    <div class="diagnostic diagnostic-{}">"#,
+            diagnostic.severity
+        )?;
+        writeln!(
+            out,
+            "        <h3>{}: {}</h3>",
+            diagnostic.severity.label(),
+            html_escape(&diagnostic.summary)
+        )?;
+        if let Some(address) = &diagnostic.address {
+            writeln!(
+                out,
+                "        <div>with <strong>{}</strong>,</div>",
+                html_escape(address)
+            )?;
+        }
+        if let Some(location) = diagnostic.location() {
+            writeln!(out, "        <div>{}</div>", html_escape(&location))?;
+        }
+        if let Some(snippet) = &diagnostic.snippet {
+            write!(out, r#"        <pre class="snippet">"#)?;
+            for line in snippet.lines() {
+                writeln!(
+                    out,
+                    r#"<span class="line-number">{}:</span> {}<mark>{}</mark>{}"#,
+                    line.number,
+                    html_escape(line.before),
+                    html_escape(line.highlight),
+                    html_escape(line.after)
+                )?;
+            }
+            for value in &snippet.values {
+                writeln!(
+                    out,
+                    "  │ <strong>{}</strong> {}",
+                    html_escape(&value.traversal),
+                    html_escape(&value.statement)
+                )?;
+            }
+            writeln!(out, "</pre>")?;
+        }
+        if !diagnostic.detail.is_empty() {
+            writeln!(out, "        <p>{}</p>", html_escape(&diagnostic.detail))?;
+        }
+        writeln!(out, "    </div>")?;
+    }
+    Ok(())
+}
+
+fn write_html_checks(out: &mut String, checks: &[CheckResult]) -> fmt::Result {
+    if checks.is_empty() {
+        return Ok(());
+    }
+    writeln!(out, r#"    <div class="checks">"#)?;
+    writeln!(
+        out,
+        "        <h3>Checks: {}</h3>",
+        format_check_counts(checks)
+    )?;
+    for check in checks.iter().filter(|check| check.status.is_failure()) {
+        writeln!(
+            out,
+            r#"        <div class="check-failed">✗ <strong>{}</strong> {}</div>"#,
+            html_escape(&check.describe()),
+            check.status.label()
+        )?;
+        for message in check.messages() {
+            writeln!(
+                out,
+                r#"        <div class="check-message">{}</div>"#,
+                html_escape(&message)
+            )?;
+        }
+    }
+    writeln!(out, "    </div>")?;
+    Ok(())
+}
+
+fn write_html_deferred(out: &mut String, deferred: &[DeferredChange]) -> fmt::Result {
+    if deferred.is_empty() {
+        return Ok(());
+    }
+    writeln!(out, r#"    <div class="deferred">"#)?;
+    writeln!(
+        out,
+        "        <h3>Deferred changes: {}</h3>",
+        html_escape(&format_deferred_counts(deferred))
+    )?;
+    writeln!(
+        out,
+        "        <p>These changes will be planned after this plan is applied.</p>"
+    )?;
+    for change in deferred {
+        writeln!(out, "        <div>{}</div>", html_escape(&change.format()))?;
+    }
+    writeln!(out, "    </div>")?;
+    Ok(())
+}
+
+fn write_html_probable_moves(out: &mut String, plan: &TerraformPlan) -> fmt::Result {
+    let moves = detect_probable_moves(plan);
+    if moves.is_empty() {
+        return Ok(());
+    }
+    writeln!(out, r#"    <div class="probable-moves">"#)?;
+    writeln!(out, "        <h3>Probable missing moved blocks</h3>")?;
+    for probable in &moves {
+        writeln!(
+            out,
+            "        <div>{}</div>",
+            html_escape(&probable.format())
+        )?;
+    }
+    writeln!(out, "    </div>")?;
+    Ok(())
+}
+
+fn write_html_stats(out: &mut String, stats: &PlanStats) -> fmt::Result {
+    writeln!(out, r#"    <div class="stats">"#)?;
+    writeln!(out, "        <h3>Statistics</h3>")?;
+    for (title, rows) in stats.tables() {
+        writeln!(out, "        <table>")?;
+        writeln!(
+            out,
+            r#"            <tr><th>{}</th><th class="create">Add</th><th class="update">Change</th><th class="replace">Replace</th><th class="destroy">Destroy</th></tr>"#,
+            title
+        )?;
+        for (name, counts) in rows {
+            writeln!(
+                out,
+                r#"            <tr><td>{}</td><td class="count">{}</td><td class="count">{}</td><td class="count">{}</td><td class="count">{}</td></tr>"#,
+                html_escape(name),
+                counts.create,
+                counts.update,
+                counts.replace,
+                counts.delete
+            )?;
+        }
+        writeln!(
+            out,
+            r#"            <tr><th>Total</th><td class="count">{}</td><td class="count">{}</td><td class="count">{}</td><td class="count">{}</td></tr>"#,
+            stats.total.create, stats.total.update, stats.total.replace, stats.total.delete
+        )?;
+        writeln!(out, "        </table>")?;
+    }
+    writeln!(out, "    </div>")?;
+    Ok(())
+}
+
+/// One resource, with `action` as its CSS class: an action, `destroy`,
+/// `import`, `move`, or `state` for a resource listed from a state snapshot.
+fn write_html_resource(
+    out: &mut String,
+    index: usize,
+    view: &ChangeView,
+    action: &str,
+    options: &FormatOptions,
+) -> fmt::Result {
+    let change = view.change;
+    let (symbol, risk, annotations) = if action == "state" {
+        let annotations = Annotations {
+            drift: None,
+            ..view.annotations()
+        };
+        ("•", RiskLevel::Low, annotations)
+    } else {
+        (view.symbol, view.risk, view.annotations())
+    };
     let action_text = match action {
         "create" => "created",
         "update" => "changed",
@@ -283,104 +1609,325 @@ fn print_html_resource(index: usize, change: &ResourceChange, action: &str, css_
         _ => action,
     };
 
-    let symbol = match action {
-        "create" => "+",
-        "update" => "~",
-        "destroy" => "-",
-        "replace" => "-/+",
-        _ => "?",
+    let description = if action == "state" {
+        String::new()
+    } else {
+        format!(" {}", html_escape(&change.describe(action_text)))
     };
 
-    println!(
+    let badge = if risk.is_high() {
+        format!(
+            r#" <span class="risk-badge risk-{}">{}</span>"#,
+            risk,
+            risk.as_str().to_uppercase()
+        )
+    } else {
+        String::new()
+    };
+    let drift_note = match annotations.drift {
+        Some(drift) => format!(
+            r#" <span class="drift-note">({})</span>"#,
+            html_escape(&drift.drift_note())
+        ),
+        None => String::new(),
+    };
+
+    writeln!(
+        out,
         r#"    <div class="resource">
         <div class="resource-header {}" id="header-{}" onclick="toggleResource({})">
-            <span class="expand-icon">▶</span> {} <strong>{}</strong> will be {}
+            <span class="expand-icon">▶</span> {} <strong>{}</strong>{}{}{}
         </div>
         <div class="details" id="details-{}">
 "#,
-        css_class, index, index, symbol, change.address, action_text, index
-    );
+        action,
+        index,
+        index,
+        symbol,
+        html_escape(&change.display_address()),
+        description,
+        badge,
+        drift_note,
+        index
+    )?;
 
-    if action == "update" || action == "replace" {
-        print_html_changes(&change.change);
-    } else if action == "create" {
-        print_html_create_attributes(&change.change);
+    if options.style == RenderStyle::Hcl && action != "state" {
+        writeln!(
+            out,
+            r#"            <pre class="hcl">{}</pre>"#,
+            html_hcl_block(change, options.show_unchanged, annotations)
+        )?;
+    } else if action == "update" || action == "replace" {
+        write_html_changes(out, &change.change, annotations)?;
+        write_html_unchanged(out, &change.change, options.show_unchanged, annotations)?;
+    } else if action == "create" || action == "state" {
+        write_html_create_attributes(out, &change.change, annotations)?;
+    }
+    let generated_config = change
+        .change
+        .generated_config
+        .as_ref()
+        .filter(|_| options.generated_config);
+    if let Some(config) = generated_config {
+        writeln!(
+            out,
+            r#"            <div class="attribute"><span class="arrow"># generated configuration:</span></div>
+            <pre class="generated-config">{}</pre>"#,
+            html_escape(config)
+        )?;
     }
 
-    println!("        </div>");
-    println!("    </div>");
+    writeln!(out, "        </div>")?;
+    writeln!(out, "    </div>")?;
+    Ok(())
 }
 
-fn print_html_changes(change: &Change) {
+fn write_html_changes(out: &mut String, change: &Change, annotations: Annotations) -> fmt::Result {
     if let (Some(before), Some(after)) = (&change.before, &change.after) {
         if let (Some(before_obj), Some(after_obj)) = (before.as_object(), after.as_object()) {
             for (key, after_val) in after_obj {
                 if let Some(before_val) = before_obj.get(key) {
                     if before_val != after_val {
-                        println!(
-                            r#"            <div class="attribute">
-                <span class="key">{}:</span> 
-                <span class="value-old">{}</span> 
-                <span class="arrow">=></span> 
-                <span class="value-new">{}</span>
-            </div>"#,
-                            html_escape(key),
-                            html_escape(&format_value(before_val)),
-                            html_escape(&format_value(after_val))
-                        );
+                        write_html_attribute_change(out, key, before_val, after_val, annotations)?;
                     }
                 } else {
-                    println!(
+                    writeln!(
+                        out,
                         r#"            <div class="attribute">
-                <span class="key">{}:</span> 
+                {} 
                 <span class="value-new">{}</span>
             </div>"#,
-                        html_escape(key),
+                        html_key(key, ":", annotations),
                         html_escape(&format_value(after_val))
-                    );
+                    )?;
                 }
             }
             for (key, before_val) in before_obj {
                 if !after_obj.contains_key(key) {
-                    println!(
+                    writeln!(
+                        out,
                         r#"            <div class="attribute">
-                <span class="key">{}:</span> 
+                {} 
                 <span class="value-old">{}</span> 
                 <span class="arrow">=></span> 
                 <span class="value-old">null</span>
             </div>"#,
-                        html_escape(key),
+                        html_key(key, ":", annotations),
                         html_escape(&format_value(before_val))
-                    );
+                    )?;
                 }
             }
         }
     }
+    Ok(())
+}
+
+/// Unchanged attributes in a `<details>` element, summarised by their counts
+/// and open when `expand` is set.
+fn write_html_unchanged(
+    out: &mut String,
+    change: &Change,
+    expand: bool,
+    annotations: Annotations,
+) -> fmt::Result {
+    let unchanged = Unchanged::from_change(change);
+    if unchanged.is_empty() {
+        return Ok(());
+    }
+    writeln!(
+        out,
+        r#"            <details class="unchanged"{}>
+                <summary>{}</summary>"#,
+        if expand { " open" } else { "" },
+        html_escape(&unchanged.format_counts().join(" "))
+    )?;
+    for (key, value) in &unchanged.attributes {
+        writeln!(
+            out,
+            r#"                <div class="attribute">
+                    {} 
+                    <span class="arrow">{}</span>
+                </div>"#,
+            html_key(key, ":", annotations),
+            html_escape(&format_value(value))
+        )?;
+    }
+    writeln!(out, "            </details>")?;
+    Ok(())
+}
+
+fn write_html_attribute_change(
+    out: &mut String,
+    key: &str,
+    before: &serde_json::Value,
+    after: &serde_json::Value,
+    annotations: Annotations,
+) -> fmt::Result {
+    if let Some(json_diff) = json_string_diff(before, after) {
+        write_html_json_string_diff(out, key, &json_diff, annotations)?;
+    } else if is_multiline_change(before, after) {
+        writeln!(
+            out,
+            r#"            <div class="attribute">
+                {}
+                <pre class="line-diff">{}</pre>
+            </div>"#,
+            html_key(key, ":", annotations),
+            html_line_diff(&multiline_diff(before, after))
+        )?;
+    } else {
+        writeln!(
+            out,
+            r#"            <div class="attribute">
+                {} 
+                <span class="value-old">{}</span> 
+                <span class="arrow">=></span> 
+                <span class="value-new">{}</span>
+            </div>"#,
+            html_key(key, ":", annotations),
+            html_escape(&format_value(before)),
+            html_escape(&format_value(after))
+        )?;
+    }
+    Ok(())
+}
+
+fn write_html_json_string_diff(
+    out: &mut String,
+    key: &str,
+    diff: &JsonStringDiff,
+    annotations: Annotations,
+) -> fmt::Result {
+    match diff {
+        JsonStringDiff::Cosmetic => writeln!(
+            out,
+            r#"            <div class="attribute cosmetic">
+                {}
+                <span class="arrow">JSON formatting only, no semantic change</span>
+            </div>"#,
+            html_key(key, ":", annotations)
+        )?,
+        JsonStringDiff::Changes(changes) => {
+            let lines: Vec<String> = changes
+                .iter()
+                .map(|change| {
+                    let class = match change {
+                        JsonChange::Changed { .. } => "value-changed",
+                        JsonChange::Added { .. } => "value-new",
+                        JsonChange::Removed { .. } => "value-old",
+                    };
+                    format!(
+                        r#"<span class="{}">{}</span>"#,
+                        class,
+                        html_escape(&change.format())
+                    )
+                })
+                .collect();
+            writeln!(
+                out,
+                r#"            <div class="attribute">
+                {} <span class="arrow">(JSON):</span>
+                <pre class="line-diff">{}</pre>
+            </div>"#,
+                html_key(key, "", annotations),
+                lines.join("\n")
+            )?;
+        }
+    }
+    Ok(())
 }
 
-fn print_html_create_attributes(change: &Change) {
+fn write_html_create_attributes(
+    out: &mut String,
+    change: &Change,
+    annotations: Annotations,
+) -> fmt::Result {
     if let Some(after) = &change.after {
         if let Some(after_obj) = after.as_object() {
             for (key, val) in after_obj {
-                println!(
+                writeln!(
+                    out,
                     r#"            <div class="attribute">
-                <span class="key">{}:</span> 
+                {} 
                 <span class="value-new">{}</span>
             </div>"#,
-                    html_escape(key),
+                    html_key(key, ":", annotations),
                     html_escape(&format_value(val))
-                );
+                )?;
             }
         }
     }
+    Ok(())
+}
+
+fn html_hcl_block(
+    change: &ResourceChange,
+    show_unchanged: bool,
+    annotations: Annotations,
+) -> String {
+    format_hcl_block(change, show_unchanged, annotations)
+        .lines()
+        .map(|line| {
+            let class = match hcl_marker(line) {
+                "+" => "value-new",
+                "-" => "value-old",
+                "~" | "-/+" => "value-changed",
+                "#" => "arrow",
+                _ => "key",
+            };
+            format!(r#"<span class="{}">{}</span>"#, class, html_escape(line))
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn html_line_diff(lines: &[DiffLine]) -> String {
+    lines
+        .iter()
+        .map(|line| match line {
+            DiffLine::Context(text) => format!("  {}", html_escape(text)),
+            DiffLine::Removed(text) => {
+                format!(r#"<span class="value-old">- {}</span>"#, html_escape(text))
+            }
+            DiffLine::Added(text) => {
+                format!(r#"<span class="value-new">+ {}</span>"#, html_escape(text))
+            }
+            DiffLine::Skipped(count) => {
+                format!(r#"<span class="arrow">{}</span>"#, format_skipped(*count))
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
 }
 
-fn html_escape(s: &str) -> String {
-    s.replace('&', "&amp;")
-        .replace('<', "&lt;")
-        .replace('>', "&gt;")
-        .replace('"', "&quot;")
-        .replace('\'', "&#x27;")
+/// An attribute name with the provider's description as a tooltip, followed
+/// by `suffix` and a note on computed or deprecated attributes.
+fn html_key(key: &str, suffix: &str, annotations: Annotations) -> String {
+    let title = annotations
+        .description(key)
+        .map(|description| format!(r#" title="{}""#, html_escape(description)))
+        .unwrap_or_default();
+    let source = annotations
+        .source(key)
+        .map(|source| {
+            format!(
+                r#" <span class="expression-source">&lt;- {}</span>"#,
+                html_escape(source)
+            )
+        })
+        .unwrap_or_default();
+    let note = annotations
+        .note(key)
+        .map(|note| format!(r#" <span class="schema-note">{}</span>"#, note))
+        .unwrap_or_default();
+    format!(
+        r#"<span class="key"{}>{}{}</span>{}{}"#,
+        title,
+        html_escape(key),
+        suffix,
+        source,
+        note
+    )
 }
 
 fn format_value(value: &serde_json::Value) -> String {
@@ -0,0 +1,209 @@
+use crate::deferred::format_deferred;
+use crate::state;
+use crate::variables::format_variables;
+use crate::{
+    detect_probable_moves, format_changes, format_checks, format_data_reads,
+    format_diagnostics_markdown, format_generated_config, format_output_changes,
+    format_probable_moves, format_resource_drift, format_summary, format_unchanged, hcl,
+    prepare_plan, FormatOptions, PlanChanges, PlanStats, RenderStyle, TerraformPlan,
+};
+use std::time::SystemTime;
+
+/// Renders a plan as Markdown for pull request comments: each change in a
+/// `<details>` element with its attributes, diagnostics with their snippets,
+/// and the summary or, with `stats`, the statistics as tables. The other
+/// sections keep their text layout in code blocks.
+pub fn format_plan_markdown(plan: &TerraformPlan, options: &FormatOptions) -> String {
+    if options.state {
+        return format!(
+            "### Terraform state\n\n{}",
+            code_block("", &state::format_state(plan, options))
+        );
+    }
+
+    let (plan, noise) = prepare_plan(plan, options);
+    let plan = &plan;
+    let report = options.risk.assess(plan);
+
+    let mut output = String::from("### Terraform plan\n\n");
+    output.push_str(&code_block(
+        "",
+        &plan
+            .metadata
+            .format_header(options.max_age, SystemTime::now()),
+    ));
+    output.push_str(&code_block(
+        "",
+        &format_variables(&plan.input_variables(), options.collapsed),
+    ));
+    output.push_str(&code_block("", &report.format_header()));
+    output.push_str(&code_block(
+        "",
+        &format_resource_drift(&plan.drift(options.all_drift)),
+    ));
+
+    let walk = PlanChanges::new(plan, options);
+    for view in &walk.changes {
+        let (change, action) = (view.change, view.action);
+        let mut summary = format!(
+            "<code>{} {}</code> {}",
+            view.symbol,
+            html_escape(&change.display_address()),
+            html_escape(&change.describe(action))
+        );
+        if view.risk.is_high() {
+            summary.push_str(&format!(" <b>[{}]</b>", view.risk.as_str().to_uppercase()));
+        }
+        let annotations = view.annotations();
+        if let Some(drifted) = annotations.drift {
+            summary.push_str(&format!(" ({})", html_escape(&drifted.drift_note())));
+        }
+        if options.collapsed {
+            output.push_str(&format!("- {}\n", summary));
+            continue;
+        }
+
+        let (language, mut body) = match options.style {
+            RenderStyle::Compact => (
+                "",
+                format_changes(&change.change, action, annotations)
+                    + &format_unchanged(&change.change, options.show_unchanged),
+            ),
+            RenderStyle::Hcl => (
+                "hcl",
+                hcl::format_hcl_block(change, options.show_unchanged, annotations),
+            ),
+        };
+        if options.generated_config {
+            body.push_str(&format_generated_config(&change.change));
+        }
+        let body = code_block(language, &body);
+        if body.is_empty() {
+            output.push_str(&format!("{}\n\n", summary));
+        } else {
+            output.push_str(&format!(
+                "<details><summary>{}</summary>\n\n{}</details>\n\n",
+                summary, body
+            ));
+        }
+    }
+    if options.collapsed && !walk.changes.is_empty() {
+        output.push('\n');
+    }
+
+    for section in [
+        format_data_reads(&walk.reads),
+        format_deferred(&plan.deferred_changes),
+        format_output_changes(&plan.output_changes),
+        format_probable_moves(&detect_probable_moves(plan)),
+        format_checks(&plan.checks),
+    ] {
+        output.push_str(&code_block("", &section));
+    }
+    output.push_str(&format_diagnostics_markdown(&plan.reported_diagnostics()));
+
+    if options.stats {
+        output.push_str(&PlanStats::from_plan(plan).format_markdown());
+    } else {
+        output.push_str(&format!(
+            "**{}**\n\n",
+            format_summary(&walk.counts).trim_end()
+        ));
+    }
+    let noise = noise.format();
+    if !noise.is_empty() {
+        output.push_str(&format!("_{}_\n\n", noise.trim_end()));
+    }
+    let score = report.format_score();
+    if !score.is_empty() {
+        output.push_str(&format!("{}\n", score.trim_end()));
+    }
+    output
+}
+
+/// `text` in a fenced code block, or nothing when it is blank. The fence is
+/// longer than any run of backticks in `text`.
+fn code_block(language: &str, text: &str) -> String {
+    let text = text.trim_matches('\n');
+    if text.trim().is_empty() {
+        return String::new();
+    }
+    let longest = text.split(|c| c != '`').map(str::len).max().unwrap_or(0);
+    let fence = "`".repeat(longest.max(2) + 1);
+    format!("{}{}\n{}\n{}\n\n", fence, language, text, fence)
+}
+
+/// `s` with the characters that are special in HTML text and attribute
+/// values escaped.
+pub fn html_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#x27;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn plan() -> TerraformPlan {
+        serde_json::from_value(json!({
+            "resource_changes": [
+                {
+                    "address": "aws_instance.web",
+                    "type": "aws_instance",
+                    "change": {"actions": ["update"], "before": {"ami": "a"}, "after": {"ami": "b"}}
+                },
+                {
+                    "address": "aws_s3_bucket.logs",
+                    "type": "aws_s3_bucket",
+                    "change": {"actions": ["delete"], "before": {"bucket": "logs"}, "after": null}
+                }
+            ],
+            "diagnostics": [{"severity": "warning", "summary": "Argument is deprecated"}]
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_format_plan_markdown() {
+        let output = format_plan_markdown(&plan(), &FormatOptions::default());
+
+        assert!(output.starts_with("### Terraform plan\n\n"));
+        assert!(output.contains(
+            "<details><summary><code>~ aws_instance.web</code> will be update</summary>\n\n\
+             ```\n        ami: \"a\" => \"b\"\n```\n\n</details>\n\n"
+        ));
+        assert!(output
+            .contains("\n<code>- aws_s3_bucket.logs</code> will be delete <b>[CRITICAL]</b>\n\n"));
+        assert!(output.contains("**Warning:** Argument is deprecated\n\n"));
+        assert!(output.contains("**Plan: 0 to add, 1 to change, 1 to destroy.**\n\n"));
+    }
+
+    #[test]
+    fn test_code_block_fence() {
+        assert_eq!(code_block("hcl", "\na = 1\n"), "```hcl\na = 1\n```\n\n");
+        assert_eq!(
+            code_block("", "x = \"```\"\ny = \"````\""),
+            "`````\nx = \"```\"\ny = \"````\"\n`````\n\n"
+        );
+        assert_eq!(code_block("", " \n"), "");
+    }
+
+    #[test]
+    fn test_collapsed_markdown_with_stats() {
+        let options = FormatOptions {
+            collapsed: true,
+            stats: true,
+            ..Default::default()
+        };
+        let output = format_plan_markdown(&plan(), &options);
+
+        assert!(output.contains("- <code>~ aws_instance.web</code> will be update\n"));
+        assert!(!output.contains("<details>"));
+        assert!(output.contains("| Resource type | Add | Change | Replace | Destroy |\n"));
+        assert!(!output.contains("**Plan:"));
+    }
+}
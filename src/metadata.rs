@@ -0,0 +1,253 @@
+use serde::Deserialize;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// What a plan document says about itself. Fields older Terraform versions
+/// do not write are `None`.
+#[derive(Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct PlanMetadata {
+    #[serde(default)]
+    pub format_version: Option<String>,
+    #[serde(default)]
+    pub terraform_version: Option<String>,
+    /// When the plan was created, in RFC 3339 format.
+    #[serde(default)]
+    pub timestamp: Option<String>,
+    /// Whether Terraform would apply the plan; false for errored plans.
+    #[serde(default)]
+    pub applyable: Option<bool>,
+    /// Whether applying the plan converges, or another plan and apply will
+    /// be needed, e.g. because of deferred changes.
+    #[serde(default)]
+    pub complete: Option<bool>,
+    /// Terraform stopped planning because of an error, so the plan is
+    /// incomplete.
+    #[serde(default)]
+    pub errored: bool,
+}
+
+impl PlanMetadata {
+    /// Seconds between the plan's timestamp and `now`, when the timestamp
+    /// can be read.
+    pub fn age(&self, now: SystemTime) -> Option<u64> {
+        let created = parse_timestamp(self.timestamp.as_deref()?)?;
+        let now = now.duration_since(UNIX_EPOCH).ok()?.as_secs() as i64;
+        Some(now.saturating_sub(created).max(0) as u64)
+    }
+
+    /// Reasons to be careful with this plan: it cannot be applied, will not
+    /// converge, or is older than `max_age` seconds.
+    pub fn warnings(&self, max_age: Option<u64>, now: SystemTime) -> Vec<String> {
+        let mut warnings = Vec::new();
+        if self.applyable == Some(false) {
+            warnings
+                .push("This plan is not applyable: Terraform will refuse to apply it.".to_string());
+        }
+        if self.complete == Some(false) {
+            warnings.push(
+                "This plan is incomplete: more changes will need to be planned after applying it."
+                    .to_string(),
+            );
+        }
+        if let (Some(max_age), Some(age)) = (max_age, self.age(now)) {
+            if age > max_age {
+                warnings.push(format!(
+                    "This plan is {} old, older than the maximum age of {}.",
+                    format_duration(age),
+                    format_duration(max_age)
+                ));
+            }
+        }
+        warnings
+    }
+
+    /// `Terraform 1.9.0 · format 1.2 · 2024-05-01T12:34:56Z (3h 5m ago) ·
+    /// applyable · complete`, or nothing when the plan carries no metadata.
+    pub fn format_summary(&self, now: SystemTime) -> String {
+        let mut parts = Vec::new();
+        if let Some(version) = &self.terraform_version {
+            parts.push(format!("Terraform {}", version));
+        }
+        if let Some(version) = &self.format_version {
+            parts.push(format!("format {}", version));
+        }
+        if let Some(timestamp) = &self.timestamp {
+            parts.push(match self.age(now) {
+                Some(age) => format!("{} ({} ago)", timestamp, format_duration(age)),
+                None => timestamp.clone(),
+            });
+        }
+        if let Some(applyable) = self.applyable {
+            parts.push(
+                if applyable {
+                    "applyable"
+                } else {
+                    "not applyable"
+                }
+                .to_string(),
+            );
+        }
+        if let Some(complete) = self.complete {
+            parts.push(if complete { "complete" } else { "incomplete" }.to_string());
+        }
+        if self.errored {
+            parts.push("errored".to_string());
+        }
+        parts.join(" · ")
+    }
+
+    /// The summary line and any warnings, followed by a blank line.
+    pub fn format_header(&self, max_age: Option<u64>, now: SystemTime) -> String {
+        let summary = self.format_summary(now);
+        let warnings = self.warnings(max_age, now);
+        if summary.is_empty() && warnings.is_empty() {
+            return String::new();
+        }
+        let mut output = String::new();
+        if !summary.is_empty() {
+            output.push_str(&summary);
+            output.push('\n');
+        }
+        for warning in warnings {
+            output.push_str(&format!("⚠ {}\n", warning));
+        }
+        output.push('\n');
+        output
+    }
+}
+
+/// Parses `90s`, `30m`, `12h` or `7d` into seconds; a bare number is seconds.
+pub fn parse_duration(text: &str) -> Result<u64, String> {
+    let text = text.trim();
+    let invalid = || {
+        format!(
+            "invalid duration '{}' (expected e.g. 90s, 30m, 12h or 7d)",
+            text
+        )
+    };
+    let (number, unit) = match text.char_indices().last() {
+        Some((index, unit)) if unit.is_ascii_alphabetic() => (&text[..index], unit),
+        _ => (text, 's'),
+    };
+    let multiplier = match unit {
+        's' => 1,
+        'm' => 60,
+        'h' => 3600,
+        'd' => 86400,
+        _ => return Err(invalid()),
+    };
+    number
+        .parse::<u64>()
+        .ok()
+        .and_then(|number| number.checked_mul(multiplier))
+        .ok_or_else(invalid)
+}
+
+/// The two largest units of a duration, e.g. `3d 4h`, `5m 2s` or `40s`.
+pub fn format_duration(seconds: u64) -> String {
+    let units = [
+        (seconds / 86400, "d"),
+        (seconds / 3600 % 24, "h"),
+        (seconds / 60 % 60, "m"),
+        (seconds % 60, "s"),
+    ];
+    let Some(first) = units.iter().position(|(value, _)| *value > 0) else {
+        return "0s".to_string();
+    };
+    units[first..]
+        .iter()
+        .take(2)
+        .filter(|(value, _)| *value > 0)
+        .map(|(value, unit)| format!("{}{}", value, unit))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Seconds since the Unix epoch for an RFC 3339 timestamp such as
+/// `2024-05-01T12:34:56Z` or `2024-05-01T14:34:56.123+02:00`.
+fn parse_timestamp(text: &str) -> Option<i64> {
+    let (date, time) = text.split_once(['T', 't'])?;
+    let mut date = date.splitn(3, '-').map(str::parse::<i64>);
+    let (year, month, day) = (date.next()?.ok()?, date.next()?.ok()?, date.next()?.ok()?);
+
+    let (time, offset) = match time.find(['Z', 'z', '+', '-']) {
+        Some(index) => time.split_at(index),
+        None => return None,
+    };
+    let offset = match offset {
+        "Z" | "z" => 0,
+        _ => {
+            let sign = if offset.starts_with('-') { -1 } else { 1 };
+            let (hours, minutes) = offset[1..].split_once(':')?;
+            sign * (hours.parse::<i64>().ok()? * 3600 + minutes.parse::<i64>().ok()? * 60)
+        }
+    };
+    let time = time.split('.').next()?;
+    let mut time = time.splitn(3, ':').map(str::parse::<i64>);
+    let (hour, minute, second) = (time.next()?.ok()?, time.next()?.ok()?, time.next()?.ok()?);
+
+    Some(days_from_civil(year, month, day) * 86400 + hour * 3600 + minute * 60 + second - offset)
+}
+
+/// Days since 1970-01-01 of a proleptic Gregorian date.
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year.rem_euclid(400);
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146097 + day_of_era - 719468
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn at(timestamp: &str) -> SystemTime {
+        UNIX_EPOCH + Duration::from_secs(parse_timestamp(timestamp).unwrap() as u64)
+    }
+
+    #[test]
+    fn test_parse_timestamp() {
+        assert_eq!(parse_timestamp("1970-01-01T00:00:00Z"), Some(0));
+        assert_eq!(parse_timestamp("2024-03-01T00:00:00Z"), Some(1709251200));
+        assert_eq!(
+            parse_timestamp("2024-03-01T02:00:00.5+02:00"),
+            Some(1709251200)
+        );
+        assert_eq!(parse_timestamp("yesterday"), None);
+    }
+
+    #[test]
+    fn test_header_and_warnings() {
+        let metadata = PlanMetadata {
+            format_version: Some("1.2".to_string()),
+            terraform_version: Some("1.9.0".to_string()),
+            timestamp: Some("2024-05-01T12:00:00Z".to_string()),
+            applyable: Some(true),
+            complete: Some(false),
+            ..Default::default()
+        };
+        let now = at("2024-05-09T15:30:00Z");
+
+        assert_eq!(
+            metadata.format_header(Some(7 * 86400), now),
+            "Terraform 1.9.0 · format 1.2 · 2024-05-01T12:00:00Z (8d 3h ago) · applyable · incomplete\n\
+             ⚠ This plan is incomplete: more changes will need to be planned after applying it.\n\
+             ⚠ This plan is 8d 3h old, older than the maximum age of 7d.\n\n"
+        );
+        assert_eq!(metadata.warnings(None, now).len(), 1);
+        assert_eq!(PlanMetadata::default().format_header(Some(60), now), "");
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("90"), Ok(90));
+        assert_eq!(parse_duration("30m"), Ok(1800));
+        assert_eq!(parse_duration("7d"), Ok(604800));
+        assert!(parse_duration("a week").is_err());
+        assert!(parse_duration("999999999999999999d").is_err());
+        assert_eq!(format_duration(3 * 3600 + 5), "3h");
+        assert_eq!(format_duration(0), "0s");
+    }
+}
@@ -0,0 +1,157 @@
+use crate::{get_action, ResourceChange, TerraformPlan};
+
+/// Minimum share of the created object's known attributes that must equal the
+/// destroyed object's for a delete/create pair to be reported.
+const MOVE_SIMILARITY_THRESHOLD: f64 = 0.8;
+
+/// A delete and a create of the same resource type with near-identical
+/// attributes, which usually means a refactor is missing a `moved` block.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProbableMove {
+    pub from: String,
+    pub to: String,
+    /// Share of attributes with equal values, between 0 and 1.
+    pub similarity: f64,
+}
+
+impl ProbableMove {
+    pub fn format(&self) -> String {
+        format!(
+            "{} -> {} ({:.0}% identical)",
+            self.from,
+            self.to,
+            self.similarity * 100.0
+        )
+    }
+}
+
+/// Finds delete/create pairs that look like the same object under a new
+/// address. Each deleted object is paired with at most one created object.
+/// Deposed objects are never paired, as their replacement already exists.
+pub fn detect_probable_moves(plan: &TerraformPlan) -> Vec<ProbableMove> {
+    let deletes: Vec<&ResourceChange> = plan
+        .resource_changes
+        .iter()
+        .filter(|c| get_action(&c.change.actions) == "delete" && c.deposed.is_none())
+        .collect();
+    let mut used = vec![false; deletes.len()];
+    let mut moves = Vec::new();
+
+    for create in plan
+        .resource_changes
+        .iter()
+        .filter(|c| get_action(&c.change.actions) == "create")
+    {
+        let best = deletes
+            .iter()
+            .enumerate()
+            .filter(|(i, delete)| !used[*i] && delete.resource_type == create.resource_type)
+            .filter_map(|(i, delete)| similarity(delete, create).map(|score| (i, score)))
+            .filter(|(_, score)| *score >= MOVE_SIMILARITY_THRESHOLD)
+            .max_by(|a, b| a.1.total_cmp(&b.1));
+
+        if let Some((i, score)) = best {
+            used[i] = true;
+            moves.push(ProbableMove {
+                from: deletes[i].address.clone(),
+                to: create.address.clone(),
+                similarity: score,
+            });
+        }
+    }
+
+    moves
+}
+
+/// Compares the known, non-null attributes of the created object with the
+/// destroyed one. Attributes that are only known after apply are absent or
+/// null in `after` and are ignored.
+fn similarity(delete: &ResourceChange, create: &ResourceChange) -> Option<f64> {
+    let before = delete.change.before.as_ref()?.as_object()?;
+    let after = create.change.after.as_ref()?.as_object()?;
+
+    let known: Vec<_> = after.iter().filter(|(_, v)| !v.is_null()).collect();
+    if known.is_empty() {
+        return None;
+    }
+    let equal = known
+        .iter()
+        .filter(|(key, val)| before.get(*key) == Some(*val))
+        .count();
+
+    Some(equal as f64 / known.len() as f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Change;
+    use serde_json::{json, Value};
+
+    fn change(address: &str, actions: &[&str], before: Value, after: Value) -> ResourceChange {
+        ResourceChange {
+            address: address.to_string(),
+            resource_type: "aws_instance".to_string(),
+            change: Change {
+                actions: actions.iter().map(|a| a.to_string()).collect(),
+                before: Some(before).filter(|v| !v.is_null()),
+                after: Some(after).filter(|v| !v.is_null()),
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_detects_delete_create_pair() {
+        let plan = TerraformPlan {
+            resource_changes: vec![
+                change(
+                    "aws_instance.web",
+                    &["delete"],
+                    json!({"ami": "ami-1", "instance_type": "t3.micro", "id": "i-1"}),
+                    Value::Null,
+                ),
+                change(
+                    "module.app.aws_instance.web",
+                    &["create"],
+                    Value::Null,
+                    json!({"ami": "ami-1", "instance_type": "t3.micro", "id": null}),
+                ),
+            ],
+            ..Default::default()
+        };
+
+        let moves = detect_probable_moves(&plan);
+        assert_eq!(moves.len(), 1);
+        assert_eq!(moves[0].from, "aws_instance.web");
+        assert_eq!(moves[0].to, "module.app.aws_instance.web");
+        assert_eq!(
+            moves[0].format(),
+            "aws_instance.web -> module.app.aws_instance.web (100% identical)"
+        );
+    }
+
+    #[test]
+    fn test_ignores_different_objects() {
+        let plan = TerraformPlan {
+            resource_changes: vec![
+                change(
+                    "aws_instance.a",
+                    &["delete"],
+                    json!({"ami": "ami-1", "instance_type": "t3.micro"}),
+                    Value::Null,
+                ),
+                change(
+                    "aws_instance.b",
+                    &["create"],
+                    Value::Null,
+                    json!({"ami": "ami-2", "instance_type": "t3.large"}),
+                ),
+            ],
+            ..Default::default()
+        };
+
+        assert!(detect_probable_moves(&plan).is_empty());
+    }
+}
@@ -0,0 +1,586 @@
+use crate::{get_action, has_unknown, NestedBlock, SchemaBlock, SchemaIndex, TerraformPlan};
+use serde_json::Value;
+use std::fmt;
+use std::str::FromStr;
+
+/// An attribute path whose differences are ignored, optionally restricted to
+/// one resource type. `resource_type` may end with `*` to match a prefix.
+#[derive(Debug, Clone, PartialEq)]
+pub struct IgnorePath {
+    pub resource_type: Option<String>,
+    /// Dotted path into the resource's attributes, e.g. `metadata.annotations`.
+    pub path: Vec<String>,
+}
+
+/// Parses `[TYPE:]PATH`, e.g. `aws_instance:user_data` or `tags_all`.
+impl FromStr for IgnorePath {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (resource_type, path) = match s.split_once(':') {
+            Some((ty, path)) => (Some(ty.trim().to_string()), path),
+            None => (None, s),
+        };
+        let path: Vec<String> = path
+            .split('.')
+            .map(|segment| segment.trim().to_string())
+            .collect();
+        if path.iter().any(|segment| segment.is_empty()) {
+            return Err(format!(
+                "invalid ignore path '{}': expected [TYPE:]ATTRIBUTE[.NESTED]",
+                s
+            ));
+        }
+        Ok(IgnorePath {
+            resource_type: resource_type.filter(|ty| !ty.is_empty() && ty != "*"),
+            path,
+        })
+    }
+}
+
+impl fmt::Display for IgnorePath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(resource_type) = &self.resource_type {
+            write!(f, "{}:", resource_type)?;
+        }
+        f.write_str(&self.path.join("."))
+    }
+}
+
+impl IgnorePath {
+    fn applies_to(&self, resource_type: &str) -> bool {
+        match &self.resource_type {
+            None => true,
+            Some(pattern) => match pattern.strip_suffix('*') {
+                Some(prefix) => resource_type.starts_with(prefix),
+                None => pattern == resource_type,
+            },
+        }
+    }
+}
+
+/// How many differences the noise filter removed from a plan.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct NoiseReport {
+    pub suppressed_attributes: usize,
+    /// Updates dropped because nothing but noise was left.
+    pub suppressed_resources: usize,
+}
+
+impl NoiseReport {
+    pub fn format(&self) -> String {
+        if self.suppressed_attributes == 0 && self.suppressed_resources == 0 {
+            return String::new();
+        }
+        format!(
+            "Suppressed {} cosmetic difference{} ({} resource{} with no remaining changes hidden).\n",
+            self.suppressed_attributes,
+            if self.suppressed_attributes == 1 { "" } else { "s" },
+            self.suppressed_resources,
+            if self.suppressed_resources == 1 { "" } else { "s" }
+        )
+    }
+}
+
+/// Removes differences that do not change infrastructure: `null` against an
+/// empty collection, reordered sets, `tags_all` mirroring `tags`, and
+/// user-configured ignore paths.
+#[derive(Debug, Clone, Default)]
+pub struct NoiseFilter {
+    ignore: Vec<IgnorePath>,
+}
+
+impl NoiseFilter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_ignore(mut self, path: IgnorePath) -> Self {
+        self.ignore.push(path);
+        self
+    }
+
+    /// Returns a copy of the plan without noise, dropping updates that have
+    /// no remaining differences. Lists are only compared regardless of order
+    /// where `schema` says they are sets. Attributes only known after apply
+    /// are never treated as noise.
+    pub fn apply(
+        &self,
+        plan: &TerraformPlan,
+        schema: Option<&SchemaIndex>,
+    ) -> (TerraformPlan, NoiseReport) {
+        let mut report = NoiseReport::default();
+        let mut filtered = plan.clone();
+
+        filtered.resource_changes.retain_mut(|change| {
+            let action = get_action(&change.change.actions);
+            if action != "update" && action != "replace" {
+                return true;
+            }
+            let shape = match schema.and_then(|schema| schema.block(change)) {
+                Some(block) => Shape::Block(block),
+                None => Shape::Unknown,
+            };
+            let (Some(Value::Object(before)), Some(Value::Object(after))) =
+                (&change.change.before, &mut change.change.after)
+            else {
+                return true;
+            };
+
+            for ignore in self
+                .ignore
+                .iter()
+                .filter(|ignore| ignore.applies_to(&change.resource_type))
+            {
+                if restore_path(before, after, &ignore.path) {
+                    report.suppressed_attributes += 1;
+                }
+            }
+
+            let unknown = &change.change.after_unknown;
+            let is_unknown = |key: &str| unknown.get(key).is_some_and(has_unknown);
+
+            let tags_all_mirrors_tags = mirrors(before, after, "tags_all", "tags");
+            let keys: Vec<String> = after.keys().cloned().collect();
+            for key in keys.into_iter().filter(|key| !is_unknown(key)) {
+                let before_val = before.get(&key);
+                let after_val = &after[&key];
+                if before_val == Some(after_val) {
+                    continue;
+                }
+                if equivalent(
+                    before_val.unwrap_or(&Value::Null),
+                    after_val,
+                    shape.member(&key),
+                ) || (key == "tags_all" && tags_all_mirrors_tags)
+                {
+                    match before_val {
+                        Some(before_val) => after.insert(key, before_val.clone()),
+                        None => after.remove(&key),
+                    };
+                    report.suppressed_attributes += 1;
+                }
+            }
+
+            let unchanged = !has_unknown(unknown)
+                && before.keys().chain(after.keys()).all(|key| {
+                    equivalent(
+                        before.get(key).unwrap_or(&Value::Null),
+                        after.get(key).unwrap_or(&Value::Null),
+                        shape.member(key),
+                    )
+                });
+            if action == "update" && unchanged {
+                report.suppressed_resources += 1;
+                return false;
+            }
+            true
+        });
+
+        (filtered, report)
+    }
+}
+
+/// Copies the value at `path` from `before` into `after`, or removes it from
+/// `after` when `before` has none, returning whether they differed.
+fn restore_path(
+    before: &serde_json::Map<String, Value>,
+    after: &mut serde_json::Map<String, Value>,
+    path: &[String],
+) -> bool {
+    let Some((key, rest)) = path.split_first() else {
+        return false;
+    };
+    if rest.is_empty() {
+        let before_val = before.get(key);
+        if after.get(key) == before_val {
+            return false;
+        }
+        match before_val {
+            Some(before_val) => after.insert(key.clone(), before_val.clone()),
+            None => after.remove(key),
+        };
+        return true;
+    }
+    match (before.get(key), after.get_mut(key)) {
+        (Some(Value::Object(b)), Some(Value::Object(a))) => restore_path(b, a, rest),
+        _ => false,
+    }
+}
+
+fn is_empty_collection(value: &Value) -> bool {
+    match value {
+        Value::Null => true,
+        Value::Array(items) => items.is_empty(),
+        Value::Object(map) => map.is_empty(),
+        _ => false,
+    }
+}
+
+/// What the schema says about a value, to tell sets from lists.
+#[derive(Debug, Clone, Copy)]
+enum Shape<'a> {
+    Unknown,
+    /// An attribute type, e.g. `["set", "string"]`.
+    Type(&'a Value),
+    Block(&'a SchemaBlock),
+    /// The blocks of a `list`, `set` or `map` block type.
+    Blocks(&'a NestedBlock),
+}
+
+impl<'a> Shape<'a> {
+    /// The shape of an attribute, nested block or map entry.
+    fn member(self, key: &str) -> Shape<'a> {
+        match self {
+            Shape::Type(ty) => match collection_type(ty) {
+                Some(("map", element)) => Shape::Type(element),
+                Some(("object", Value::Object(fields))) => {
+                    fields.get(key).map_or(Shape::Unknown, Shape::Type)
+                }
+                _ => Shape::Unknown,
+            },
+            Shape::Block(block) => {
+                if let Some(attribute) = block.attributes.get(key) {
+                    return Shape::Type(&attribute.attribute_type);
+                }
+                match block.block_types.get(key) {
+                    Some(nested) if matches!(nested.nesting_mode.as_str(), "single" | "group") => {
+                        Shape::Block(&nested.block)
+                    }
+                    Some(nested) => Shape::Blocks(nested),
+                    None => Shape::Unknown,
+                }
+            }
+            Shape::Blocks(nested) if nested.nesting_mode == "map" => Shape::Block(&nested.block),
+            _ => Shape::Unknown,
+        }
+    }
+
+    /// The shape of a list or set element.
+    fn element(self) -> Shape<'a> {
+        match self {
+            Shape::Type(ty) => match collection_type(ty) {
+                Some(("list" | "set", element)) => Shape::Type(element),
+                _ => Shape::Unknown,
+            },
+            Shape::Blocks(nested) => Shape::Block(&nested.block),
+            _ => Shape::Unknown,
+        }
+    }
+
+    fn is_set(self) -> bool {
+        match self {
+            Shape::Type(ty) => matches!(collection_type(ty), Some(("set", _))),
+            Shape::Blocks(nested) => nested.nesting_mode == "set",
+            _ => false,
+        }
+    }
+}
+
+/// `("set", "string")` for the type `["set", "string"]`.
+fn collection_type(ty: &Value) -> Option<(&str, &Value)> {
+    match ty.as_array()?.as_slice() {
+        [kind, element] => Some((kind.as_str()?, element)),
+        _ => None,
+    }
+}
+
+/// Equality that treats `null` and empty collections alike and recurses into
+/// objects. Lists are compared in order.
+pub fn normalized_eq(a: &Value, b: &Value) -> bool {
+    equivalent(a, b, Shape::Unknown)
+}
+
+/// [`normalized_eq`], ignoring the order of the elements of sets.
+fn equivalent(a: &Value, b: &Value, shape: Shape) -> bool {
+    if is_empty_collection(a) && is_empty_collection(b) {
+        return true;
+    }
+    match (a, b) {
+        (Value::Object(a), Value::Object(b)) => a.keys().chain(b.keys()).all(|key| {
+            equivalent(
+                a.get(key).unwrap_or(&Value::Null),
+                b.get(key).unwrap_or(&Value::Null),
+                shape.member(key),
+            )
+        }),
+        (Value::Array(a), Value::Array(b)) => {
+            if a.len() != b.len() {
+                return false;
+            }
+            let element = shape.element();
+            if !shape.is_set() {
+                return a.iter().zip(b).all(|(a, b)| equivalent(a, b, element));
+            }
+            let mut unmatched: Vec<&Value> = b.iter().collect();
+            a.iter().all(|item| {
+                match unmatched
+                    .iter()
+                    .position(|candidate| equivalent(item, candidate, element))
+                {
+                    Some(i) => {
+                        unmatched.swap_remove(i);
+                        true
+                    }
+                    None => false,
+                }
+            })
+        }
+        _ => a == b,
+    }
+}
+
+/// Whether every difference in the `mirror` map also appears, with the same
+/// values, in the `source` map, as AWS provider `tags_all` does for `tags`.
+fn mirrors(
+    before: &serde_json::Map<String, Value>,
+    after: &serde_json::Map<String, Value>,
+    mirror: &str,
+    source: &str,
+) -> bool {
+    let empty = serde_json::Map::new();
+    let object = |map: &serde_json::Map<String, Value>, key: &str| {
+        map.get(key)
+            .and_then(Value::as_object)
+            .cloned()
+            .unwrap_or_else(|| empty.clone())
+    };
+    let (mirror_before, mirror_after) = (object(before, mirror), object(after, mirror));
+    let (source_before, source_after) = (object(before, source), object(after, source));
+
+    mirror_before
+        .keys()
+        .chain(mirror_after.keys())
+        .filter(|key| mirror_before.get(*key) != mirror_after.get(*key))
+        .all(|key| {
+            source_before.get(key) == mirror_before.get(key)
+                && source_after.get(key) == mirror_after.get(key)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Change, ResourceChange};
+    use serde_json::json;
+
+    fn update(resource_type: &str, before: Value, after: Value) -> ResourceChange {
+        ResourceChange {
+            address: format!("{}.example", resource_type),
+            resource_type: resource_type.to_string(),
+            change: Change {
+                actions: vec!["update".to_string()],
+                before: Some(before),
+                after: Some(after),
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    fn schema() -> SchemaIndex {
+        SchemaIndex::parse(
+            &json!({
+                "provider_schemas": {
+                    "registry.terraform.io/hashicorp/aws": {
+                        "resource_schemas": {
+                            "aws_security_group": {
+                                "block": {
+                                    "attributes": {
+                                        "cidr_blocks": {"type": ["set", "string"], "optional": true},
+                                        "command": {"type": ["list", "string"], "optional": true}
+                                    },
+                                    "block_types": {
+                                        "ingress": {
+                                            "nesting_mode": "set",
+                                            "block": {
+                                                "attributes": {
+                                                    "ports": {"type": ["list", "number"], "optional": true}
+                                                }
+                                            }
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            })
+            .to_string(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_normalized_eq() {
+        assert!(normalized_eq(&json!(null), &json!([])));
+        assert!(normalized_eq(&json!({}), &json!(null)));
+        assert!(!normalized_eq(&json!(["a", "b"]), &json!(["b", "a"])));
+        assert!(normalized_eq(
+            &json!({"rules": [{"port": 80}], "extra": null}),
+            &json!({"rules": [{"port": 80}]})
+        ));
+        assert!(!normalized_eq(&json!(["a", "a"]), &json!(["a", "b"])));
+        assert!(!normalized_eq(&json!(""), &json!(null)));
+    }
+
+    #[test]
+    fn test_only_sets_ignore_order() {
+        let schema = schema();
+        let shape = Shape::Block(
+            schema
+                .block(&update("aws_security_group", json!({}), json!({})))
+                .unwrap(),
+        );
+        let eq = |key: &str, a: Value, b: Value| equivalent(&a, &b, shape.member(key));
+
+        assert!(eq("cidr_blocks", json!(["a", "b"]), json!(["b", "a"])));
+        assert!(!eq("command", json!(["--a", "--b"]), json!(["--b", "--a"])));
+        assert!(!eq("unknown", json!(["a", "b"]), json!(["b", "a"])));
+        assert!(eq(
+            "ingress",
+            json!([{"ports": [80, 443]}, {"ports": [22]}]),
+            json!([{"ports": [22]}, {"ports": [80, 443]}])
+        ));
+        assert!(!eq(
+            "ingress",
+            json!([{"ports": [80, 443]}]),
+            json!([{"ports": [443, 80]}])
+        ));
+    }
+
+    #[test]
+    fn test_drops_cosmetic_only_updates() {
+        let plan = TerraformPlan {
+            resource_changes: vec![
+                update(
+                    "aws_security_group",
+                    json!({"cidr_blocks": ["10.0.0.0/8", "192.168.0.0/16"], "ingress": null}),
+                    json!({"cidr_blocks": ["192.168.0.0/16", "10.0.0.0/8"], "ingress": []}),
+                ),
+                update(
+                    "aws_instance",
+                    json!({"instance_type": "t3.micro", "tags": {"a": "1"}, "tags_all": {"a": "1"}}),
+                    json!({"instance_type": "t3.large", "tags": {"a": "2"}, "tags_all": {"a": "2"}}),
+                ),
+            ],
+            ..Default::default()
+        };
+
+        let (filtered, report) = NoiseFilter::new().apply(&plan, Some(&schema()));
+
+        assert_eq!(filtered.resource_changes.len(), 1);
+        assert_eq!(
+            filtered.resource_changes[0].change.after,
+            Some(json!({"instance_type": "t3.large", "tags": {"a": "2"}, "tags_all": {"a": "1"}}))
+        );
+        assert_eq!(
+            report,
+            NoiseReport {
+                suppressed_attributes: 3,
+                suppressed_resources: 1,
+            }
+        );
+
+        let (filtered, _) = NoiseFilter::new().apply(&plan, None);
+        assert_eq!(filtered.resource_changes.len(), 2);
+    }
+
+    #[test]
+    fn test_keeps_attributes_known_after_apply() {
+        let mut change = update(
+            "aws_security_group",
+            json!({"ids": [], "name": "a"}),
+            json!({"name": "a"}),
+        );
+        change.change.after_unknown = json!({"ids": true});
+        let plan = TerraformPlan {
+            resource_changes: vec![change],
+            ..Default::default()
+        };
+
+        let (filtered, report) = NoiseFilter::new().apply(&plan, Some(&schema()));
+
+        assert_eq!(filtered.resource_changes.len(), 1);
+        assert_eq!(
+            filtered.resource_changes[0].change.after,
+            Some(json!({"name": "a"}))
+        );
+        assert_eq!(report, NoiseReport::default());
+    }
+
+    #[test]
+    fn test_added_empty_attribute_is_removed() {
+        let plan = TerraformPlan {
+            resource_changes: vec![update(
+                "aws_instance",
+                json!({"instance_type": "t3.micro"}),
+                json!({"instance_type": "t3.large", "extra": []}),
+            )],
+            ..Default::default()
+        };
+
+        let (filtered, report) = NoiseFilter::new().apply(&plan, None);
+
+        assert_eq!(
+            filtered.resource_changes[0].change.after,
+            Some(json!({"instance_type": "t3.large"}))
+        );
+        assert_eq!(report.suppressed_attributes, 1);
+    }
+
+    #[test]
+    fn test_ignore_paths() {
+        let plan = TerraformPlan {
+            resource_changes: vec![update(
+                "kubernetes_deployment",
+                json!({"metadata": {"annotations": {"rev": "1"}, "name": "web"}}),
+                json!({"metadata": {"annotations": {"rev": "2"}, "name": "web"}}),
+            )],
+            ..Default::default()
+        };
+
+        let ignore: IgnorePath = "kubernetes_*:metadata.annotations".parse().unwrap();
+        let (filtered, report) = NoiseFilter::new().with_ignore(ignore).apply(&plan, None);
+
+        assert!(filtered.resource_changes.is_empty());
+        assert_eq!(report.suppressed_attributes, 1);
+        assert!(report
+            .format()
+            .contains("Suppressed 1 cosmetic difference (1 resource"));
+
+        let other: IgnorePath = "aws_instance:metadata".parse().unwrap();
+        let (filtered, _) = NoiseFilter::new().with_ignore(other).apply(&plan, None);
+        assert_eq!(filtered.resource_changes.len(), 1);
+    }
+
+    #[test]
+    fn test_report_counts_hidden_resources() {
+        let plan = TerraformPlan {
+            resource_changes: vec![update(
+                "aws_instance",
+                json!({"ami": "ami-1"}),
+                json!({"ami": "ami-1"}),
+            )],
+            ..Default::default()
+        };
+
+        let (filtered, report) = NoiseFilter::new().apply(&plan, None);
+
+        assert!(filtered.resource_changes.is_empty());
+        assert_eq!(
+            report.format(),
+            "Suppressed 0 cosmetic differences (1 resource with no remaining changes hidden).\n"
+        );
+        assert_eq!(NoiseReport::default().format(), "");
+    }
+
+    #[test]
+    fn test_parse_ignore_path() {
+        let path: IgnorePath = "tags_all".parse().unwrap();
+        assert_eq!(path.resource_type, None);
+        assert_eq!(path.path, vec!["tags_all"]);
+        assert!("aws_instance:".parse::<IgnorePath>().is_err());
+        assert!("a..b".parse::<IgnorePath>().is_err());
+    }
+}
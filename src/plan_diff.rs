@@ -0,0 +1,265 @@
+use crate::{changed_attributes, get_action, Change, InputVariable, ResourceChange, TerraformPlan};
+
+/// How one resource change differs between two plans of the same
+/// configuration, e.g. before and after a rebase.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PlanDiffEntry {
+    /// Only the new plan changes this object.
+    Added { address: String, action: String },
+    /// Only the old plan changes this object.
+    Removed { address: String, action: String },
+    ActionChanged {
+        address: String,
+        before: String,
+        after: String,
+    },
+    /// Same action, but the planned values of these attributes differ.
+    ValuesChanged {
+        address: String,
+        attributes: Vec<String>,
+    },
+    /// An input variable was set differently, or only in one of the plans.
+    /// Values are as displayed, so sensitive ones are masked.
+    VariableChanged {
+        name: String,
+        before: Option<String>,
+        after: Option<String>,
+    },
+}
+
+impl PlanDiffEntry {
+    pub fn format(&self) -> String {
+        match self {
+            PlanDiffEntry::Added { address, action } => {
+                format!("+ {} ({}, only in new plan)", address, action)
+            }
+            PlanDiffEntry::Removed { address, action } => {
+                format!("- {} ({}, only in old plan)", address, action)
+            }
+            PlanDiffEntry::ActionChanged {
+                address,
+                before,
+                after,
+            } => format!("~ {}: {} -> {}", address, before, after),
+            PlanDiffEntry::ValuesChanged {
+                address,
+                attributes,
+            } => format!(
+                "~ {}: planned values changed ({})",
+                address,
+                attributes.join(", ")
+            ),
+            PlanDiffEntry::VariableChanged {
+                name,
+                before,
+                after,
+            } => match (before, after) {
+                (Some(before), Some(after)) => format!("~ var.{}: {} -> {}", name, before, after),
+                (None, Some(after)) => format!("+ var.{} = {} (only in new plan)", name, after),
+                (Some(before), None) => format!("- var.{} = {} (only in old plan)", name, before),
+                (None, None) => format!("~ var.{}", name),
+            },
+        }
+    }
+}
+
+/// Compares the input variables and then the resource changes of two plans,
+/// in the order of `new` with changes only found in `old` last. Data source
+/// reads are ignored.
+pub fn diff_plans(old: &TerraformPlan, new: &TerraformPlan) -> Vec<PlanDiffEntry> {
+    let mut entries = diff_variables(old, new);
+    let (old, new) = (keyed_changes(old), keyed_changes(new));
+    for (address, change) in &new {
+        let action = get_action(&change.change.actions).to_string();
+        let Some(previous) = find(&old, address) else {
+            entries.push(PlanDiffEntry::Added {
+                address: address.clone(),
+                action,
+            });
+            continue;
+        };
+        let previous_action = get_action(&previous.change.actions);
+        if previous_action != action {
+            entries.push(PlanDiffEntry::ActionChanged {
+                address: address.clone(),
+                before: previous_action.to_string(),
+                after: action,
+            });
+            continue;
+        }
+        let attributes = changed_attributes(&Change {
+            before: previous.change.after.clone(),
+            after: change.change.after.clone(),
+            ..Default::default()
+        });
+        if !attributes.is_empty() {
+            entries.push(PlanDiffEntry::ValuesChanged {
+                address: address.clone(),
+                attributes,
+            });
+        }
+    }
+    for (address, change) in &old {
+        if find(&new, address).is_none() {
+            entries.push(PlanDiffEntry::Removed {
+                address: address.clone(),
+                action: get_action(&change.change.actions).to_string(),
+            });
+        }
+    }
+    entries
+}
+
+fn diff_variables(old: &TerraformPlan, new: &TerraformPlan) -> Vec<PlanDiffEntry> {
+    let (old, new) = (old.input_variables(), new.input_variables());
+    let mut names: Vec<&String> = old.iter().chain(&new).map(|v| &v.name).collect();
+    names.sort();
+    names.dedup();
+    names
+        .into_iter()
+        .filter_map(|name| {
+            let before = old.iter().find(|v| &v.name == name);
+            let after = new.iter().find(|v| &v.name == name);
+            if before.map(|v| &v.value) == after.map(|v| &v.value) {
+                return None;
+            }
+            Some(PlanDiffEntry::VariableChanged {
+                name: name.clone(),
+                before: before.map(InputVariable::display_value),
+                after: after.map(InputVariable::display_value),
+            })
+        })
+        .collect()
+}
+
+/// Changes other than data source reads, keyed by their display address so
+/// deposed objects and moves are told apart.
+fn keyed_changes(plan: &TerraformPlan) -> Vec<(String, &ResourceChange)> {
+    plan.resource_changes
+        .iter()
+        .filter(|change| !change.is_data_read())
+        .map(|change| (change.display_address(), change))
+        .collect()
+}
+
+fn find<'a>(changes: &[(String, &'a ResourceChange)], address: &str) -> Option<&'a ResourceChange> {
+    changes
+        .iter()
+        .find(|(key, _)| key == address)
+        .map(|(_, change)| *change)
+}
+
+/// One line per difference, or a note that the plans make the same changes.
+pub fn format_plan_diff(entries: &[PlanDiffEntry]) -> String {
+    if entries.is_empty() {
+        return "The plans make the same changes.\n".to_string();
+    }
+    let mut output = String::new();
+    for entry in entries {
+        output.push_str(&entry.format());
+        output.push('\n');
+    }
+    output.push_str(&format!(
+        "\n{} difference{} between the plans.\n",
+        entries.len(),
+        if entries.len() == 1 { "" } else { "s" }
+    ));
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{json, Value};
+
+    fn change(address: &str, actions: &[&str], after: Value) -> ResourceChange {
+        ResourceChange {
+            address: address.to_string(),
+            change: Change {
+                actions: actions.iter().map(|a| a.to_string()).collect(),
+                after: Some(after),
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_diff_plans() {
+        let old = TerraformPlan {
+            resource_changes: vec![
+                change("aws_instance.web", &["update"], json!({"ami": "ami-1"})),
+                change("aws_s3_bucket.logs", &["update"], json!({"acl": "private"})),
+                change("aws_iam_role.old", &["delete"], Value::Null),
+                change("aws_vpc.main", &["create"], json!({"cidr": "10.0.0.0/16"})),
+            ],
+            ..Default::default()
+        };
+        let new = TerraformPlan {
+            resource_changes: vec![
+                change("aws_instance.web", &["update"], json!({"ami": "ami-2"})),
+                change(
+                    "aws_s3_bucket.logs",
+                    &["delete", "create"],
+                    json!({"acl": "private"}),
+                ),
+                change("aws_vpc.main", &["create"], json!({"cidr": "10.0.0.0/16"})),
+                change("aws_subnet.a", &["create"], json!({})),
+            ],
+            ..Default::default()
+        };
+
+        let formatted: Vec<String> = diff_plans(&old, &new)
+            .iter()
+            .map(PlanDiffEntry::format)
+            .collect();
+        assert_eq!(
+            formatted,
+            vec![
+                "~ aws_instance.web: planned values changed (ami)",
+                "~ aws_s3_bucket.logs: update -> replace",
+                "+ aws_subnet.a (create, only in new plan)",
+                "- aws_iam_role.old (delete, only in old plan)",
+            ]
+        );
+        assert_eq!(
+            format_plan_diff(&diff_plans(&old, &old)),
+            "The plans make the same changes.\n"
+        );
+    }
+
+    #[test]
+    fn test_diff_variables() {
+        let plan = |variables: Value| -> TerraformPlan {
+            serde_json::from_value(json!({
+                "resource_changes": [],
+                "variables": variables,
+                "configuration": {"root_module": {"variables": {"token": {"sensitive": true}}}}
+            }))
+            .unwrap()
+        };
+        let old = plan(json!({
+            "region": {"value": "eu-west-1"},
+            "token": {"value": "a"},
+            "size": {"value": 2}
+        }));
+        let new = plan(json!({
+            "region": {"value": "eu-west-1"},
+            "token": {"value": "b"},
+            "env": {"value": "prod"}
+        }));
+
+        let formatted: Vec<String> = diff_plans(&old, &new)
+            .iter()
+            .map(PlanDiffEntry::format)
+            .collect();
+        assert_eq!(
+            formatted,
+            vec![
+                "+ var.env = \"prod\" (only in new plan)",
+                "- var.size = 2 (only in old plan)",
+                "~ var.token: (sensitive value) -> (sensitive value)",
+            ]
+        );
+    }
+}
@@ -0,0 +1,408 @@
+use crate::{changed_attributes, get_action, ResourceChange, TerraformPlan};
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum RiskLevel {
+    Low,
+    Medium,
+    High,
+    Critical,
+}
+
+impl RiskLevel {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RiskLevel::Low => "low",
+            RiskLevel::Medium => "medium",
+            RiskLevel::High => "high",
+            RiskLevel::Critical => "critical",
+        }
+    }
+
+    /// Weight contributed by a single change to the overall plan score.
+    pub fn weight(&self) -> u32 {
+        match self {
+            RiskLevel::Low => 1,
+            RiskLevel::Medium => 3,
+            RiskLevel::High => 7,
+            RiskLevel::Critical => 15,
+        }
+    }
+
+    pub fn is_high(&self) -> bool {
+        *self >= RiskLevel::High
+    }
+}
+
+impl fmt::Display for RiskLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for RiskLevel {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "low" => Ok(RiskLevel::Low),
+            "medium" => Ok(RiskLevel::Medium),
+            "high" => Ok(RiskLevel::High),
+            "critical" => Ok(RiskLevel::Critical),
+            other => Err(format!("unknown risk level '{}'", other)),
+        }
+    }
+}
+
+/// A single classification rule. Empty `actions` or `attributes` match anything;
+/// `resource_type` may end with `*` to match a prefix.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RiskRule {
+    pub resource_type: String,
+    pub actions: Vec<String>,
+    pub attributes: Vec<String>,
+    pub level: RiskLevel,
+}
+
+impl RiskRule {
+    pub fn new(resource_type: &str, actions: &[&str], level: RiskLevel) -> Self {
+        RiskRule {
+            resource_type: resource_type.to_string(),
+            actions: actions.iter().map(|a| a.to_string()).collect(),
+            attributes: Vec::new(),
+            level,
+        }
+    }
+
+    pub fn with_attributes(mut self, attributes: &[&str]) -> Self {
+        self.attributes = attributes.iter().map(|a| a.to_string()).collect();
+        self
+    }
+
+    fn matches(&self, resource_type: &str, action: &str, changed: &[String]) -> bool {
+        let type_matches = match self.resource_type.strip_suffix('*') {
+            Some(prefix) => resource_type.starts_with(prefix),
+            None => self.resource_type == resource_type,
+        };
+        let action_matches = self.actions.is_empty() || self.actions.iter().any(|a| a == action);
+        let attribute_matches =
+            self.attributes.is_empty() || changed.iter().any(|c| self.attributes.contains(c));
+
+        type_matches && action_matches && attribute_matches
+    }
+}
+
+/// Parses `TYPE[:ACTION,...][@ATTRIBUTE,...]=LEVEL`, e.g. `aws_s3_bucket:delete=critical`.
+impl FromStr for RiskRule {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (selector, level) = s
+            .rsplit_once('=')
+            .ok_or_else(|| format!("invalid risk rule '{}': expected TYPE=LEVEL", s))?;
+        let level = level.trim().parse()?;
+
+        let (selector, attributes) = match selector.split_once('@') {
+            Some((selector, attrs)) => (selector, split_list(attrs)),
+            None => (selector, Vec::new()),
+        };
+        let (resource_type, actions) = match selector.split_once(':') {
+            Some((ty, actions)) => (ty, split_list(actions)),
+            None => (selector, Vec::new()),
+        };
+
+        if resource_type.trim().is_empty() {
+            return Err(format!("invalid risk rule '{}': missing resource type", s));
+        }
+
+        Ok(RiskRule {
+            resource_type: resource_type.trim().to_string(),
+            actions,
+            attributes,
+            level,
+        })
+    }
+}
+
+impl fmt::Display for RiskRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.resource_type)?;
+        if !self.actions.is_empty() {
+            write!(f, ":{}", self.actions.join(","))?;
+        }
+        if !self.attributes.is_empty() {
+            write!(f, "@{}", self.attributes.join(","))?;
+        }
+        write!(f, "={}", self.level)
+    }
+}
+
+fn split_list(s: &str) -> Vec<String> {
+    s.split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Attributes whose change alone never makes an update risky.
+const COSMETIC_ATTRIBUTES: &[&str] = &["tags", "tags_all", "labels", "description"];
+
+/// Resource types holding data that is lost when the resource is destroyed.
+const STATEFUL_TYPES: &[&str] = &[
+    "aws_s3_bucket",
+    "aws_db_instance",
+    "aws_rds_cluster",
+    "aws_dynamodb_table",
+    "aws_efs_file_system",
+    "aws_ebs_volume",
+    "aws_elasticache_cluster",
+    "aws_kms_key",
+    "aws_route53_zone",
+    "google_sql_database_instance",
+    "google_storage_bucket",
+    "google_compute_disk",
+    "google_kms_crypto_key",
+    "azurerm_storage_account",
+    "azurerm_mssql_database",
+    "azurerm_postgresql_server",
+    "azurerm_key_vault",
+];
+
+/// Resource types controlling access, where any modification deserves attention.
+const ACCESS_TYPES: &[&str] = &[
+    "aws_iam_*",
+    "aws_security_group",
+    "aws_security_group_rule",
+    "aws_vpc_security_group_*",
+    "google_project_iam_*",
+    "google_compute_firewall",
+    "azurerm_role_assignment",
+    "azurerm_network_security_*",
+];
+
+#[derive(Debug, Clone)]
+pub struct RiskClassifier {
+    overrides: Vec<RiskRule>,
+    builtin: Vec<RiskRule>,
+}
+
+impl Default for RiskClassifier {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RiskClassifier {
+    /// Creates a classifier with the built-in rule table.
+    pub fn new() -> Self {
+        let mut builtin = Vec::new();
+        for ty in STATEFUL_TYPES {
+            builtin.push(RiskRule::new(
+                ty,
+                &["delete", "replace"],
+                RiskLevel::Critical,
+            ));
+        }
+        for ty in ACCESS_TYPES {
+            builtin.push(RiskRule::new(ty, &["update", "replace"], RiskLevel::High));
+            builtin.push(RiskRule::new(ty, &["delete"], RiskLevel::High));
+        }
+
+        RiskClassifier {
+            overrides: Vec::new(),
+            builtin,
+        }
+    }
+
+    /// Adds a user rule. User rules are checked before the built-in table,
+    /// in the order they were added.
+    pub fn with_rule(mut self, rule: RiskRule) -> Self {
+        self.overrides.push(rule);
+        self
+    }
+
+    pub fn classify(&self, change: &ResourceChange) -> RiskLevel {
+        let action = get_action(&change.change.actions);
+        let changed = changed_attributes(&change.change);
+
+        let matched = self
+            .overrides
+            .iter()
+            .chain(self.builtin.iter())
+            .find(|rule| rule.matches(&change.resource_type, action, &changed));
+
+        match matched {
+            Some(rule) => rule.level,
+            None => default_level(action, &changed),
+        }
+    }
+
+    pub fn assess(&self, plan: &TerraformPlan) -> RiskReport {
+        let mut report = RiskReport::default();
+        for change in &plan.resource_changes {
+            if matches!(get_action(&change.change.actions), "unknown" | "read") {
+                continue;
+            }
+            let level = self.classify(change);
+            report.score += level.weight();
+            report.level = report.level.max(Some(level));
+            if level.is_high() {
+                report.high_risk.push((change.display_address(), level));
+            }
+        }
+        report
+            .high_risk
+            .sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        report
+    }
+}
+
+fn default_level(action: &str, changed: &[String]) -> RiskLevel {
+    match action {
+        "delete" | "replace" => RiskLevel::High,
+        "update"
+            if changed
+                .iter()
+                .all(|c| COSMETIC_ATTRIBUTES.contains(&c.as_str())) =>
+        {
+            RiskLevel::Low
+        }
+        "update" => RiskLevel::Medium,
+        _ => RiskLevel::Low,
+    }
+}
+
+/// Overall risk of a plan.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RiskReport {
+    /// Sum of the weights of every change.
+    pub score: u32,
+    /// Highest level of any change, `None` for an empty plan.
+    pub level: Option<RiskLevel>,
+    /// High and critical changes, most dangerous first.
+    pub high_risk: Vec<(String, RiskLevel)>,
+}
+
+impl RiskReport {
+    pub fn format_header(&self) -> String {
+        if self.high_risk.is_empty() {
+            return String::new();
+        }
+        let mut output = String::from("⚠ High-risk changes:\n");
+        for (address, level) in &self.high_risk {
+            output.push_str(&format!(
+                "    [{}] {}\n",
+                level.as_str().to_uppercase(),
+                address
+            ));
+        }
+        output.push('\n');
+        output
+    }
+
+    pub fn format_score(&self) -> String {
+        match self.level {
+            Some(level) => format!("Risk: {} (score {})\n", level, self.score),
+            None => String::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Change;
+    use serde_json::json;
+
+    fn change(resource_type: &str, actions: &[&str]) -> ResourceChange {
+        ResourceChange {
+            address: format!("{}.example", resource_type),
+            resource_type: resource_type.to_string(),
+            change: Change {
+                actions: actions.iter().map(|a| a.to_string()).collect(),
+                before: Some(json!({"tags": {"env": "dev"}, "size": 1})),
+                after: Some(json!({"tags": {"env": "prod"}, "size": 1})),
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_builtin_rules() {
+        let classifier = RiskClassifier::new();
+        assert_eq!(
+            classifier.classify(&change("aws_s3_bucket", &["delete"])),
+            RiskLevel::Critical
+        );
+        assert_eq!(
+            classifier.classify(&change(
+                "google_sql_database_instance",
+                &["delete", "create"]
+            )),
+            RiskLevel::Critical
+        );
+        assert_eq!(
+            classifier.classify(&change("aws_iam_role_policy", &["update"])),
+            RiskLevel::High
+        );
+        assert_eq!(
+            classifier.classify(&change("aws_instance", &["update"])),
+            RiskLevel::Low
+        );
+        assert_eq!(
+            classifier.classify(&change("aws_instance", &["create"])),
+            RiskLevel::Low
+        );
+    }
+
+    #[test]
+    fn test_override_takes_precedence() {
+        let rule: RiskRule = "aws_s3_bucket:delete=medium".parse().unwrap();
+        let classifier = RiskClassifier::new().with_rule(rule);
+        assert_eq!(
+            classifier.classify(&change("aws_s3_bucket", &["delete"])),
+            RiskLevel::Medium
+        );
+    }
+
+    #[test]
+    fn test_parse_rule() {
+        let rule: RiskRule = "aws_instance:update,delete@user_data=high".parse().unwrap();
+        assert_eq!(rule.resource_type, "aws_instance");
+        assert_eq!(rule.actions, vec!["update", "delete"]);
+        assert_eq!(rule.attributes, vec!["user_data"]);
+        assert_eq!(rule.level, RiskLevel::High);
+        assert_eq!(
+            rule.to_string(),
+            "aws_instance:update,delete@user_data=high"
+        );
+
+        assert!("aws_instance".parse::<RiskRule>().is_err());
+        assert!("aws_instance=extreme".parse::<RiskRule>().is_err());
+    }
+
+    #[test]
+    fn test_assess() {
+        let plan = TerraformPlan {
+            resource_changes: vec![
+                change("aws_instance", &["create"]),
+                change("aws_s3_bucket", &["delete"]),
+            ],
+            ..Default::default()
+        };
+        let report = RiskClassifier::new().assess(&plan);
+        assert_eq!(report.score, 16);
+        assert_eq!(report.level, Some(RiskLevel::Critical));
+        assert_eq!(
+            report.high_risk,
+            vec![("aws_s3_bucket.example".to_string(), RiskLevel::Critical)]
+        );
+        assert!(report
+            .format_header()
+            .contains("[CRITICAL] aws_s3_bucket.example"));
+        assert!(report.format_score().contains("Risk: critical (score 16)"));
+    }
+}
@@ -0,0 +1,389 @@
+use crate::unchanged::is_blank;
+use crate::{Diagnostic, ResourceChange, Severity, TerraformPlan};
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::Path;
+
+/// The output of `terraform providers schema -json`.
+#[derive(Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct ProviderSchemas {
+    #[serde(default)]
+    pub format_version: Option<String>,
+    /// Keyed by provider source address, e.g.
+    /// `registry.terraform.io/hashicorp/aws`.
+    #[serde(default)]
+    pub provider_schemas: BTreeMap<String, ProviderSchema>,
+}
+
+#[derive(Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct ProviderSchema {
+    #[serde(default)]
+    pub resource_schemas: BTreeMap<String, Schema>,
+    #[serde(default)]
+    pub data_source_schemas: BTreeMap<String, Schema>,
+}
+
+#[derive(Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct Schema {
+    #[serde(default)]
+    pub version: u64,
+    pub block: SchemaBlock,
+}
+
+/// The attributes and nested blocks of a resource, data source or block.
+#[derive(Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct SchemaBlock {
+    #[serde(default)]
+    pub attributes: BTreeMap<String, SchemaAttribute>,
+    #[serde(default)]
+    pub block_types: BTreeMap<String, NestedBlock>,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub deprecated: bool,
+}
+
+#[derive(Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct SchemaAttribute {
+    /// The type, e.g. `"string"` or `["list", "string"]`.
+    #[serde(rename = "type", default)]
+    pub attribute_type: Value,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub required: bool,
+    #[serde(default)]
+    pub optional: bool,
+    /// Set by the provider; optional attributes can be both.
+    #[serde(default)]
+    pub computed: bool,
+    #[serde(default)]
+    pub deprecated: bool,
+    #[serde(default)]
+    pub sensitive: bool,
+}
+
+impl SchemaAttribute {
+    /// Whether only the provider sets this attribute.
+    pub fn is_read_only(&self) -> bool {
+        self.computed && !self.optional && !self.required
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct NestedBlock {
+    /// `single`, `group`, `list`, `set` or `map`.
+    pub nesting_mode: String,
+    pub block: SchemaBlock,
+    #[serde(default)]
+    pub min_items: u64,
+    #[serde(default)]
+    pub max_items: u64,
+}
+
+impl NestedBlock {
+    /// The blocks in a value of this block type, with the key of each block
+    /// for `map` nesting: a `single` or `group` block is an object, a `map`
+    /// an object of objects, and the others a list of objects.
+    pub fn items<'a>(&self, value: &'a Value) -> Vec<(Option<&'a str>, &'a Value)> {
+        match (self.nesting_mode.as_str(), value) {
+            ("single" | "group", Value::Object(_)) => vec![(None, value)],
+            ("map", Value::Object(map)) => map
+                .iter()
+                .map(|(key, item)| (Some(key.as_str()), item))
+                .collect(),
+            (_, Value::Array(items)) => items.iter().map(|item| (None, item)).collect(),
+            _ => Vec::new(),
+        }
+    }
+}
+
+impl SchemaBlock {
+    /// What to note about an attribute or block next to its value: `computed`
+    /// for attributes only the provider sets, and `deprecated`.
+    pub fn note(&self, name: &str) -> Option<String> {
+        let mut notes = Vec::new();
+        if let Some(attribute) = self.attributes.get(name) {
+            if attribute.is_read_only() {
+                notes.push("computed");
+            }
+            if attribute.deprecated {
+                notes.push("deprecated");
+            }
+        } else if self
+            .block_types
+            .get(name)
+            .is_some_and(|nested| nested.block.deprecated)
+        {
+            notes.push("deprecated");
+        }
+        (!notes.is_empty()).then(|| notes.join(", "))
+    }
+
+    /// The provider's description of an attribute or block.
+    pub fn description(&self, name: &str) -> Option<&str> {
+        match self.attributes.get(name) {
+            Some(attribute) => attribute.description.as_deref(),
+            None => self.block_types.get(name)?.block.description.as_deref(),
+        }
+        .filter(|description| !description.is_empty())
+    }
+
+    /// Paths of the deprecated attributes and blocks `after` sets, e.g.
+    /// `ebs_block_device.iops`. A deprecated attribute the provider also
+    /// computes counts only when its value changes, since the provider may
+    /// have set it.
+    fn deprecated_uses(&self, before: Option<&Value>, after: &Value, prefix: &str) -> Vec<String> {
+        let mut uses = Vec::new();
+        let Value::Object(object) = after else {
+            return uses;
+        };
+        for (name, value) in object {
+            if is_blank(value) {
+                continue;
+            }
+            let path = format!("{}{}", prefix, name);
+            let previous = before.and_then(|before| before.get(name));
+            if let Some(attribute) = self.attributes.get(name) {
+                if attribute.deprecated && (!attribute.computed || previous != Some(value)) {
+                    uses.push(path);
+                }
+            } else if let Some(nested) = self.block_types.get(name) {
+                if nested.block.deprecated {
+                    uses.push(path);
+                    continue;
+                }
+                let before_items = previous.map(|previous| nested.items(previous));
+                for (index, (key, item)) in nested.items(value).into_iter().enumerate() {
+                    let before_item = before_items
+                        .as_ref()
+                        .and_then(|items| items.get(index))
+                        .map(|(_, item)| *item);
+                    let prefix = match key {
+                        Some(key) => format!("{}[\"{}\"].", path, key),
+                        None => format!("{}.", path),
+                    };
+                    uses.extend(nested.block.deprecated_uses(before_item, item, &prefix));
+                }
+            }
+        }
+        uses.dedup();
+        uses
+    }
+}
+
+/// Provider schemas indexed by resource mode and type, to look up the
+/// schema of each resource change.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SchemaIndex {
+    /// Keyed by `managed` or `data`, then type, then provider source address.
+    blocks: HashMap<(String, String), BTreeMap<String, SchemaBlock>>,
+}
+
+impl SchemaIndex {
+    pub fn new(schemas: ProviderSchemas) -> Self {
+        let mut blocks: HashMap<(String, String), BTreeMap<String, SchemaBlock>> = HashMap::new();
+        for (provider, schema) in schemas.provider_schemas {
+            for (mode, types) in [
+                ("managed", schema.resource_schemas),
+                ("data", schema.data_source_schemas),
+            ] {
+                for (resource_type, schema) in types {
+                    blocks
+                        .entry((mode.to_string(), resource_type))
+                        .or_default()
+                        .insert(provider.clone(), schema.block);
+                }
+            }
+        }
+        SchemaIndex { blocks }
+    }
+
+    /// Parses the output of `terraform providers schema -json`.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let schemas: ProviderSchemas = serde_json::from_str(text).map_err(|e| e.to_string())?;
+        Ok(Self::new(schemas))
+    }
+
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text = fs::read_to_string(path)
+            .map_err(|e| format!("cannot read {}: {}", path.display(), e))?;
+        Self::parse(&text).map_err(|e| format!("invalid schema {}: {}", path.display(), e))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.blocks.is_empty()
+    }
+
+    /// The schema of a resource change. Changes from the UI stream carry no
+    /// provider, and then any provider's schema for the type is used.
+    pub fn block(&self, change: &ResourceChange) -> Option<&SchemaBlock> {
+        let mode = if change.mode == "data" {
+            "data"
+        } else {
+            "managed"
+        };
+        let providers = self
+            .blocks
+            .get(&(mode.to_string(), change.resource_type.clone()))?;
+        providers
+            .get(&change.provider_name)
+            .or_else(|| providers.values().next())
+    }
+
+    /// Adds [`deprecation_warnings`](Self::deprecation_warnings) to the plan's
+    /// diagnostics.
+    pub fn annotate(&self, plan: &mut TerraformPlan) {
+        let warnings = self.deprecation_warnings(plan);
+        plan.diagnostics.extend(warnings);
+    }
+
+    /// A warning for each resource change that sets deprecated attributes or
+    /// blocks.
+    pub fn deprecation_warnings(&self, plan: &TerraformPlan) -> Vec<Diagnostic> {
+        plan.resource_changes
+            .iter()
+            .filter_map(|change| {
+                let block = self.block(change)?;
+                let after = change.change.after.as_ref()?;
+                let uses = block.deprecated_uses(change.change.before.as_ref(), after, "");
+                let (summary, noun) = match uses.len() {
+                    0 => return None,
+                    1 => ("Deprecated attribute", "is"),
+                    _ => ("Deprecated attributes", "are"),
+                };
+                let names: Vec<String> = uses.iter().map(|path| format!("\"{}\"", path)).collect();
+                Some(Diagnostic {
+                    severity: Severity::Warning,
+                    summary: summary.to_string(),
+                    detail: format!(
+                        "{} {} deprecated in the {} schema.",
+                        names.join(", "),
+                        noun,
+                        change.resource_type
+                    ),
+                    address: Some(change.address.clone()),
+                    ..Default::default()
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn schema() -> SchemaIndex {
+        SchemaIndex::parse(
+            &json!({
+                "format_version": "1.0",
+                "provider_schemas": {
+                    "registry.terraform.io/hashicorp/aws": {
+                        "resource_schemas": {
+                            "aws_instance": {
+                                "version": 1,
+                                "block": {
+                                    "attributes": {
+                                        "ami": {"type": "string", "required": true, "description": "AMI to use."},
+                                        "arn": {"type": "string", "computed": true},
+                                        "cpu_core_count": {"type": "number", "optional": true, "computed": true, "deprecated": true},
+                                        "tags": {"type": ["map", "string"], "optional": true}
+                                    },
+                                    "block_types": {
+                                        "root_block_device": {
+                                            "nesting_mode": "list",
+                                            "max_items": 1,
+                                            "block": {
+                                                "attributes": {
+                                                    "iops": {"type": "number", "optional": true, "deprecated": true},
+                                                    "volume_size": {"type": "number", "optional": true}
+                                                }
+                                            }
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            })
+            .to_string(),
+        )
+        .unwrap()
+    }
+
+    fn change(value: Value) -> ResourceChange {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn test_block_lookup() {
+        let schema = schema();
+        let mut web = change(json!({
+            "address": "aws_instance.web",
+            "mode": "managed",
+            "type": "aws_instance",
+            "provider_name": "registry.terraform.io/hashicorp/aws",
+            "change": {"actions": ["create"]}
+        }));
+        let block = schema.block(&web).unwrap();
+        assert_eq!(block.note("arn").as_deref(), Some("computed"));
+        assert_eq!(block.note("cpu_core_count").as_deref(), Some("deprecated"));
+        assert_eq!(block.note("ami"), None);
+        assert_eq!(block.description("ami"), Some("AMI to use."));
+
+        web.provider_name.clear();
+        assert!(schema.block(&web).is_some());
+        web.mode = "data".to_string();
+        assert!(schema.block(&web).is_none());
+    }
+
+    #[test]
+    fn test_deprecation_warnings() {
+        let plan: TerraformPlan = serde_json::from_value(json!({
+            "resource_changes": [
+                {
+                    "address": "aws_instance.web",
+                    "type": "aws_instance",
+                    "provider_name": "registry.terraform.io/hashicorp/aws",
+                    "change": {
+                        "actions": ["update"],
+                        "before": {"ami": "a", "cpu_core_count": 2, "root_block_device": [{"iops": 100}]},
+                        "after": {"ami": "b", "cpu_core_count": 2, "root_block_device": [{"iops": 100}]}
+                    }
+                },
+                {
+                    "address": "aws_instance.db",
+                    "type": "aws_instance",
+                    "provider_name": "registry.terraform.io/hashicorp/aws",
+                    "change": {"actions": ["create"], "before": null, "after": {"ami": "b", "cpu_core_count": 4}}
+                }
+            ]
+        }))
+        .unwrap();
+
+        let warnings = schema().deprecation_warnings(&plan);
+        let details: Vec<(&str, &str)> = warnings
+            .iter()
+            .map(|w| (w.address.as_deref().unwrap(), w.detail.as_str()))
+            .collect();
+        assert_eq!(
+            details,
+            [
+                (
+                    "aws_instance.web",
+                    "\"root_block_device.iops\" is deprecated in the aws_instance schema."
+                ),
+                (
+                    "aws_instance.db",
+                    "\"cpu_core_count\" is deprecated in the aws_instance schema."
+                )
+            ]
+        );
+        assert!(warnings.iter().all(|w| w.severity == Severity::Warning));
+    }
+}
@@ -0,0 +1,193 @@
+use crate::{changed_attributes, get_action, ResourceChange};
+use std::fmt;
+use std::str::FromStr;
+
+/// Order in which resource changes are rendered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SortOrder {
+    /// Critical, then high-risk changes first, and the order Terraform
+    /// emitted otherwise. Ranking needs the risk classifier, so it is left to
+    /// [`PlanChanges`](crate::PlanChanges); `sort_changes` keeps plan order.
+    #[default]
+    Risk,
+    /// Keep the order Terraform emitted.
+    Plan,
+    /// Most destructive first: delete, replace, update, create.
+    Action,
+    Address,
+    Module,
+    Type,
+    /// Most changed attributes first.
+    Changes,
+}
+
+impl SortOrder {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SortOrder::Risk => "risk",
+            SortOrder::Plan => "plan",
+            SortOrder::Action => "action",
+            SortOrder::Address => "address",
+            SortOrder::Module => "module",
+            SortOrder::Type => "type",
+            SortOrder::Changes => "changes",
+        }
+    }
+}
+
+impl fmt::Display for SortOrder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for SortOrder {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "risk" => Ok(SortOrder::Risk),
+            "plan" | "none" => Ok(SortOrder::Plan),
+            "action" | "severity" => Ok(SortOrder::Action),
+            "address" => Ok(SortOrder::Address),
+            "module" => Ok(SortOrder::Module),
+            "type" => Ok(SortOrder::Type),
+            "changes" => Ok(SortOrder::Changes),
+            other => Err(format!(
+                "unknown sort order '{}' (expected risk, plan, action, address, module, type or changes)",
+                other
+            )),
+        }
+    }
+}
+
+fn action_severity(change: &ResourceChange) -> u8 {
+    match get_action(&change.change.actions) {
+        "delete" => 0,
+        "replace" => 1,
+        "update" => 2,
+        "create" => 3,
+        _ => 4,
+    }
+}
+
+/// Returns the plan's resource changes in the requested order. The sort is
+/// stable and ties are broken by address, except for `SortOrder::Plan` and
+/// `SortOrder::Risk`.
+pub fn sort_changes(changes: &[ResourceChange], order: SortOrder) -> Vec<&ResourceChange> {
+    let mut sorted: Vec<&ResourceChange> = changes.iter().collect();
+
+    match order {
+        SortOrder::Risk | SortOrder::Plan => {}
+        SortOrder::Action => sorted.sort_by(|a, b| {
+            action_severity(a)
+                .cmp(&action_severity(b))
+                .then_with(|| a.address.cmp(&b.address))
+        }),
+        SortOrder::Address => sorted.sort_by(|a, b| a.address.cmp(&b.address)),
+        SortOrder::Module => sorted.sort_by(|a, b| {
+            a.module_address
+                .cmp(&b.module_address)
+                .then_with(|| a.address.cmp(&b.address))
+        }),
+        SortOrder::Type => sorted.sort_by(|a, b| {
+            a.resource_type
+                .cmp(&b.resource_type)
+                .then_with(|| a.address.cmp(&b.address))
+        }),
+        SortOrder::Changes => sorted.sort_by_cached_key(|change| {
+            (
+                std::cmp::Reverse(changed_attributes(&change.change).len()),
+                change.address.clone(),
+            )
+        }),
+    }
+
+    sorted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Change;
+    use serde_json::json;
+
+    fn change(address: &str, module: Option<&str>, actions: &[&str]) -> ResourceChange {
+        ResourceChange {
+            address: address.to_string(),
+            resource_type: address.rsplit('.').nth(1).unwrap_or_default().to_string(),
+            module_address: module.map(str::to_string),
+            change: Change {
+                actions: actions.iter().map(|a| a.to_string()).collect(),
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    fn addresses(changes: Vec<&ResourceChange>) -> Vec<&str> {
+        changes.into_iter().map(|c| c.address.as_str()).collect()
+    }
+
+    fn sample() -> Vec<ResourceChange> {
+        vec![
+            change("module.web.aws_instance.b", Some("module.web"), &["create"]),
+            change("aws_s3_bucket.a", None, &["update"]),
+            change("aws_instance.c", None, &["delete"]),
+            change("aws_vpc.d", None, &["delete", "create"]),
+        ]
+    }
+
+    #[test]
+    fn test_sort_by_action() {
+        let changes = sample();
+        assert_eq!(
+            addresses(sort_changes(&changes, SortOrder::Action)),
+            vec![
+                "aws_instance.c",
+                "aws_vpc.d",
+                "aws_s3_bucket.a",
+                "module.web.aws_instance.b"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_sort_by_module_and_type() {
+        let changes = sample();
+        assert_eq!(
+            addresses(sort_changes(&changes, SortOrder::Module)),
+            vec![
+                "aws_instance.c",
+                "aws_s3_bucket.a",
+                "aws_vpc.d",
+                "module.web.aws_instance.b"
+            ]
+        );
+        assert_eq!(
+            addresses(sort_changes(&changes, SortOrder::Type)),
+            vec![
+                "aws_instance.c",
+                "module.web.aws_instance.b",
+                "aws_s3_bucket.a",
+                "aws_vpc.d"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_sort_by_changes() {
+        let mut changes = sample();
+        changes[1].change.before = Some(json!({"a": 1, "b": 1}));
+        changes[1].change.after = Some(json!({"a": 2, "b": 2}));
+        let sorted = addresses(sort_changes(&changes, SortOrder::Changes));
+        assert_eq!(sorted[0], "aws_s3_bucket.a");
+    }
+
+    #[test]
+    fn test_parse_sort_order() {
+        assert_eq!("action".parse::<SortOrder>(), Ok(SortOrder::Action));
+        assert_eq!("Address".parse::<SortOrder>(), Ok(SortOrder::Address));
+        assert!("size".parse::<SortOrder>().is_err());
+    }
+}
@@ -0,0 +1,297 @@
+use crate::{
+    format_changes, sort_changes, Annotations, Change, FormatOptions, ResourceChange, TerraformPlan,
+};
+use serde::Deserialize;
+use serde_json::Value;
+
+/// Output of `terraform show -json` without a plan file.
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct TerraformState {
+    /// Absent when the state holds no resources.
+    #[serde(default)]
+    pub values: Option<StateValues>,
+}
+
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct StateValues {
+    pub root_module: StateModule,
+}
+
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct StateModule {
+    #[serde(default)]
+    pub address: Option<String>,
+    #[serde(default)]
+    pub resources: Vec<StateResource>,
+    #[serde(default)]
+    pub child_modules: Vec<StateModule>,
+}
+
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct StateResource {
+    pub address: String,
+    #[serde(rename = "type", default)]
+    pub resource_type: String,
+    #[serde(default)]
+    pub provider_name: String,
+    #[serde(default)]
+    pub values: Value,
+    #[serde(default)]
+    pub sensitive_values: Value,
+}
+
+/// Placeholder shown instead of attributes marked sensitive.
+pub const SENSITIVE_PLACEHOLDER: &str = "(sensitive value)";
+
+impl TerraformState {
+    /// All resources, depth first through `child_modules`, each paired with
+    /// the address of the module containing it (`None` for the root module).
+    pub fn resources(&self) -> Vec<(Option<&str>, &StateResource)> {
+        let mut resources = Vec::new();
+        if let Some(values) = &self.values {
+            collect_resources(&values.root_module, &mut resources);
+        }
+        resources
+    }
+
+    /// Converts the state into a plan of `no-op` changes, so the plan renderers
+    /// can display it. Sensitive attributes are masked.
+    pub fn to_plan(&self) -> TerraformPlan {
+        let resource_changes = self
+            .resources()
+            .into_iter()
+            .map(|(module, resource)| {
+                let values = mask_sensitive(&resource.values, &resource.sensitive_values);
+                ResourceChange {
+                    address: resource.address.clone(),
+                    resource_type: resource.resource_type.clone(),
+                    provider_name: resource.provider_name.clone(),
+                    module_address: module.map(str::to_string),
+                    change: Change {
+                        actions: vec!["no-op".to_string()],
+                        before: Some(values.clone()),
+                        after: Some(values),
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                }
+            })
+            .collect();
+
+        TerraformPlan {
+            resource_changes,
+            ..Default::default()
+        }
+    }
+}
+
+fn collect_resources<'a>(
+    module: &'a StateModule,
+    resources: &mut Vec<(Option<&'a str>, &'a StateResource)>,
+) {
+    for resource in &module.resources {
+        resources.push((module.address.as_deref(), resource));
+    }
+    for child in &module.child_modules {
+        collect_resources(child, resources);
+    }
+}
+
+/// Replaces every value `sensitive` marks `true` with the placeholder,
+/// following it into objects by key and arrays by index.
+fn mask_sensitive(values: &Value, sensitive: &Value) -> Value {
+    match (values, sensitive) {
+        (_, Value::Bool(true)) => Value::String(SENSITIVE_PLACEHOLDER.to_string()),
+        (Value::Object(values), Value::Object(sensitive)) => values
+            .iter()
+            .map(|(key, value)| {
+                let masked = match sensitive.get(key) {
+                    Some(sensitive) => mask_sensitive(value, sensitive),
+                    None => value.clone(),
+                };
+                (key.clone(), masked)
+            })
+            .collect::<serde_json::Map<_, _>>()
+            .into(),
+        (Value::Array(values), Value::Array(sensitive)) => values
+            .iter()
+            .enumerate()
+            .map(|(index, value)| match sensitive.get(index) {
+                Some(sensitive) => mask_sensitive(value, sensitive),
+                None => value.clone(),
+            })
+            .collect(),
+        _ => values.clone(),
+    }
+}
+
+/// Renders a plan produced by [`TerraformState::to_plan`]: resource headers
+/// followed by their current attributes.
+pub(crate) fn format_state(plan: &TerraformPlan, options: &FormatOptions) -> String {
+    let indicator = if options.collapsed { "▶" } else { "▼" };
+    let mut output = String::new();
+
+    for change in sort_changes(&plan.resource_changes, options.sort) {
+        output.push_str(&format!("{} {}\n", indicator, change.address));
+        if !options.collapsed {
+            let annotations = Annotations {
+                schema: options
+                    .schema
+                    .as_ref()
+                    .and_then(|schema| schema.block(change)),
+                ..Default::default()
+            };
+            output.push_str(&format_changes(&change.change, "create", annotations));
+        }
+        output.push('\n');
+    }
+
+    output.push_str(&format_state_summary(plan));
+    output
+}
+
+/// `State: N resources in M modules.`
+pub fn format_state_summary(plan: &TerraformPlan) -> String {
+    let mut modules: Vec<Option<&str>> = plan
+        .resource_changes
+        .iter()
+        .map(|change| change.module_address.as_deref())
+        .collect();
+    modules.sort();
+    modules.dedup();
+
+    match plan.resource_changes.len() {
+        0 => "State is empty.\n".to_string(),
+        1 => "State: 1 resource in 1 module.\n".to_string(),
+        count => format!(
+            "State: {} resources in {} module{}.\n",
+            count,
+            modules.len(),
+            if modules.len() == 1 { "" } else { "s" }
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn sample() -> TerraformState {
+        serde_json::from_value(json!({
+            "format_version": "1.0",
+            "terraform_version": "1.6.0",
+            "values": {
+                "root_module": {
+                    "resources": [{
+                        "address": "aws_db_instance.main",
+                        "mode": "managed",
+                        "type": "aws_db_instance",
+                        "name": "main",
+                        "provider_name": "registry.terraform.io/hashicorp/aws",
+                        "values": {"engine": "postgres", "password": "hunter2"},
+                        "sensitive_values": {"password": true}
+                    }],
+                    "child_modules": [{
+                        "address": "module.network",
+                        "resources": [{
+                            "address": "module.network.aws_vpc.main",
+                            "type": "aws_vpc",
+                            "values": {"cidr_block": "10.0.0.0/16"}
+                        }],
+                        "child_modules": [{
+                            "address": "module.network.module.subnets",
+                            "resources": [{
+                                "address": "module.network.module.subnets.aws_subnet.a",
+                                "type": "aws_subnet",
+                                "values": {"cidr_block": "10.0.1.0/24"}
+                            }]
+                        }]
+                    }]
+                }
+            }
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_resources_recurse_into_child_modules() {
+        let state = sample();
+        let resources = state.resources();
+
+        assert_eq!(resources.len(), 3);
+        assert_eq!(resources[0].0, None);
+        assert_eq!(resources[1].0, Some("module.network"));
+        assert_eq!(
+            resources[2].1.address,
+            "module.network.module.subnets.aws_subnet.a"
+        );
+    }
+
+    #[test]
+    fn test_to_plan_masks_sensitive_values() {
+        let plan = sample().to_plan();
+        let db = &plan.resource_changes[0];
+
+        assert_eq!(db.change.actions, vec!["no-op"]);
+        assert_eq!(
+            db.change.after,
+            Some(json!({"engine": "postgres", "password": SENSITIVE_PLACEHOLDER}))
+        );
+        assert_eq!(
+            format_state_summary(&plan),
+            "State: 3 resources in 3 modules.\n"
+        );
+    }
+
+    #[test]
+    fn test_nested_sensitive_values_are_masked() {
+        let masked = mask_sensitive(
+            &json!({
+                "name": "app",
+                "settings": {"api_key": "nested-secret", "region": "eu-west-1"},
+                "tokens": ["list-secret", "public"],
+                "rules": [{"secret": "deep-secret", "port": 443}]
+            }),
+            &json!({
+                "settings": {"api_key": true},
+                "tokens": [true],
+                "rules": [{"secret": true}]
+            }),
+        );
+
+        assert_eq!(
+            masked,
+            json!({
+                "name": "app",
+                "settings": {"api_key": SENSITIVE_PLACEHOLDER, "region": "eu-west-1"},
+                "tokens": [SENSITIVE_PLACEHOLDER, "public"],
+                "rules": [{"secret": SENSITIVE_PLACEHOLDER, "port": 443}]
+            })
+        );
+    }
+
+    #[test]
+    fn test_format_state() {
+        let options = FormatOptions {
+            state: true,
+            ..Default::default()
+        };
+        let output = crate::format_plan_with_options(&sample().to_plan(), &options);
+
+        assert!(output.contains("▼ aws_db_instance.main\n        engine: \"postgres\"\n"));
+        assert!(output.contains("password: \"(sensitive value)\""));
+        assert!(output.contains("▼ module.network.aws_vpc.main\n"));
+        assert!(!output.contains("will be"));
+        assert!(output.ends_with("State: 3 resources in 3 modules.\n"));
+    }
+
+    #[test]
+    fn test_empty_state() {
+        let state: TerraformState =
+            serde_json::from_value(json!({"format_version": "1.0"})).unwrap();
+
+        assert!(state.resources().is_empty());
+        assert_eq!(format_state_summary(&state.to_plan()), "State is empty.\n");
+    }
+}
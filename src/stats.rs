@@ -0,0 +1,251 @@
+use crate::{format_import_count, get_action, TerraformPlan};
+use std::collections::BTreeMap;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ActionCounts {
+    pub create: usize,
+    pub update: usize,
+    pub replace: usize,
+    pub delete: usize,
+}
+
+impl ActionCounts {
+    fn record(&mut self, action: &str) {
+        match action {
+            "create" => self.create += 1,
+            "update" => self.update += 1,
+            "replace" => self.replace += 1,
+            "delete" => self.delete += 1,
+            _ => {}
+        }
+    }
+
+    pub fn total(&self) -> usize {
+        self.create + self.update + self.replace + self.delete
+    }
+}
+
+/// Breakdown of planned actions per resource type, provider and module.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PlanStats {
+    pub total: ActionCounts,
+    pub imports: usize,
+    pub by_type: BTreeMap<String, ActionCounts>,
+    pub by_provider: BTreeMap<String, ActionCounts>,
+    pub by_module: BTreeMap<String, ActionCounts>,
+}
+
+pub const ROOT_MODULE: &str = "(root)";
+
+/// Strips the default registry host, so `registry.terraform.io/hashicorp/aws`
+/// becomes `hashicorp/aws`.
+pub fn short_provider_name(provider: &str) -> &str {
+    provider
+        .strip_prefix("registry.terraform.io/")
+        .unwrap_or(provider)
+}
+
+impl PlanStats {
+    pub fn from_plan(plan: &TerraformPlan) -> Self {
+        let mut stats = PlanStats::default();
+
+        for change in &plan.resource_changes {
+            if change.is_importing() {
+                stats.imports += 1;
+            }
+            let action = get_action(&change.change.actions);
+            if action == "unknown" {
+                continue;
+            }
+            let provider = if change.provider_name.is_empty() {
+                "(unknown)"
+            } else {
+                short_provider_name(&change.provider_name)
+            };
+            let module = change.module_address.as_deref().unwrap_or(ROOT_MODULE);
+
+            stats.total.record(action);
+            stats
+                .by_type
+                .entry(change.resource_type.clone())
+                .or_default()
+                .record(action);
+            stats
+                .by_provider
+                .entry(provider.to_string())
+                .or_default()
+                .record(action);
+            stats
+                .by_module
+                .entry(module.to_string())
+                .or_default()
+                .record(action);
+        }
+
+        stats
+    }
+
+    /// Named tables in display order.
+    pub fn tables(&self) -> [(&'static str, &BTreeMap<String, ActionCounts>); 3] {
+        [
+            ("Resource type", &self.by_type),
+            ("Provider", &self.by_provider),
+            ("Module", &self.by_module),
+        ]
+    }
+
+    pub fn format_text(&self) -> String {
+        if self.total.total() == 0 && self.imports == 0 {
+            return "No changes. Your infrastructure matches the configuration.\n".to_string();
+        }
+
+        let mut output = String::new();
+        for (title, rows) in self.tables() {
+            let width = rows
+                .keys()
+                .map(|k| k.chars().count())
+                .chain([title.len(), "Total".len()])
+                .max()
+                .unwrap_or(0);
+
+            output.push_str(&format!(
+                "{:<width$}  {:>6}  {:>6}  {:>7}  {:>7}\n",
+                title,
+                "add",
+                "change",
+                "replace",
+                "destroy",
+                width = width
+            ));
+            for (name, counts) in rows {
+                output.push_str(&format_text_row(name, counts, width));
+            }
+            output.push_str(&format_text_row("Total", &self.total, width));
+            output.push('\n');
+        }
+        output.push_str(&self.format_summary());
+        output
+    }
+
+    /// `Plan: N to add, N to change, N to destroy.`, or Terraform's no
+    /// changes message.
+    pub fn format_summary(&self) -> String {
+        if self.total.total() == 0 && self.imports == 0 {
+            return "No changes. Your infrastructure matches the configuration.\n".to_string();
+        }
+        format!(
+            "Plan: {}{} to add, {} to change, {} to destroy.\n",
+            format_import_count(self.imports),
+            self.total.create,
+            self.total.update + self.total.replace,
+            self.total.delete
+        )
+    }
+
+    pub fn format_markdown(&self) -> String {
+        let mut output = String::new();
+        for (title, rows) in self.tables() {
+            output.push_str(&format!(
+                "| {} | Add | Change | Replace | Destroy |\n",
+                title
+            ));
+            output.push_str("|---|---:|---:|---:|---:|\n");
+            for (name, counts) in rows {
+                output.push_str(&format_markdown_row(&format!("`{}`", name), counts));
+            }
+            output.push_str(&format_markdown_row("**Total**", &self.total));
+            output.push('\n');
+        }
+        output
+    }
+}
+
+fn format_text_row(name: &str, counts: &ActionCounts, width: usize) -> String {
+    format!(
+        "{:<width$}  {:>6}  {:>6}  {:>7}  {:>7}\n",
+        name,
+        counts.create,
+        counts.update,
+        counts.replace,
+        counts.delete,
+        width = width
+    )
+}
+
+fn format_markdown_row(name: &str, counts: &ActionCounts) -> String {
+    format!(
+        "| {} | {} | {} | {} | {} |\n",
+        name, counts.create, counts.update, counts.replace, counts.delete
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Change, ResourceChange};
+
+    fn change(resource_type: &str, module: Option<&str>, actions: &[&str]) -> ResourceChange {
+        ResourceChange {
+            address: format!("{}.example", resource_type),
+            resource_type: resource_type.to_string(),
+            provider_name: "registry.terraform.io/hashicorp/aws".to_string(),
+            module_address: module.map(str::to_string),
+            change: Change {
+                actions: actions.iter().map(|a| a.to_string()).collect(),
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    fn sample() -> TerraformPlan {
+        TerraformPlan {
+            resource_changes: vec![
+                change("aws_instance", None, &["create"]),
+                change("aws_instance", Some("module.web"), &["delete", "create"]),
+                change("aws_s3_bucket", Some("module.web"), &["delete"]),
+                change("aws_vpc", None, &["no-op"]),
+            ],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_from_plan() {
+        let stats = PlanStats::from_plan(&sample());
+
+        assert_eq!(stats.total.total(), 3);
+        assert_eq!(
+            stats.by_type["aws_instance"],
+            ActionCounts {
+                create: 1,
+                replace: 1,
+                ..Default::default()
+            }
+        );
+        assert_eq!(stats.by_provider["hashicorp/aws"].total(), 3);
+        assert_eq!(stats.by_module[ROOT_MODULE].total(), 1);
+        assert_eq!(stats.by_module["module.web"].delete, 1);
+        assert!(!stats.by_type.contains_key("aws_vpc"));
+    }
+
+    #[test]
+    fn test_format_text() {
+        let output = PlanStats::from_plan(&sample()).format_text();
+
+        assert!(output.contains("Resource type     add  change  replace  destroy"));
+        assert!(output.contains("aws_instance        1       0        1        0"));
+        assert!(output.contains("hashicorp/aws"));
+        assert!(output.contains("module.web"));
+        assert!(output.ends_with("Plan: 1 to add, 1 to change, 1 to destroy.\n"));
+    }
+
+    #[test]
+    fn test_format_markdown() {
+        let output = PlanStats::from_plan(&sample()).format_markdown();
+
+        assert!(output.contains("| Module | Add | Change | Replace | Destroy |"));
+        assert!(output.contains("| `aws_s3_bucket` | 0 | 0 | 0 | 1 |"));
+        assert!(output.contains("| **Total** | 1 | 0 | 1 | 1 |"));
+    }
+}
@@ -0,0 +1,207 @@
+use crate::{Change, Diagnostic, Importing, ResourceChange, TerraformPlan};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+
+/// One line of the `terraform plan -json` UI stream. Only the fields of the
+/// message types the formatter understands are read.
+#[derive(Deserialize)]
+struct Message {
+    #[serde(rename = "@timestamp", default)]
+    timestamp: Option<String>,
+    #[serde(rename = "type", default)]
+    kind: String,
+    /// The Terraform version, in `version` messages.
+    #[serde(default)]
+    terraform: Option<String>,
+    #[serde(default)]
+    change: Option<StreamChange>,
+    #[serde(default)]
+    outputs: BTreeMap<String, StreamOutput>,
+    #[serde(default)]
+    diagnostic: Option<Diagnostic>,
+}
+
+#[derive(Deserialize)]
+pub(crate) struct StreamChange {
+    resource: StreamResource,
+    #[serde(default)]
+    previous_resource: Option<StreamResource>,
+    action: String,
+    #[serde(default)]
+    reason: Option<String>,
+    #[serde(default)]
+    importing: Option<Importing>,
+    #[serde(default)]
+    generated_config: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub(crate) struct StreamResource {
+    pub(crate) addr: String,
+    #[serde(default)]
+    module: String,
+    /// The address within its module, e.g. `data.aws_ami.ubuntu`.
+    #[serde(default)]
+    resource: String,
+    #[serde(default)]
+    resource_type: String,
+    #[serde(default)]
+    resource_name: String,
+    #[serde(default)]
+    implied_provider: String,
+}
+
+#[derive(Deserialize)]
+struct StreamOutput {
+    #[serde(default)]
+    sensitive: bool,
+    action: String,
+}
+
+/// Whether `text` is the `-json` UI stream rather than a `terraform show
+/// -json` document: its first line is a message with a `@message` field.
+pub fn is_plan_stream(text: &str) -> bool {
+    text.lines()
+        .find(|line| !line.trim().is_empty())
+        .and_then(|line| serde_json::from_str::<serde_json::Value>(line).ok())
+        .is_some_and(|message| message.get("@message").is_some())
+}
+
+/// Builds a plan from the `terraform plan -json` UI stream, using its
+/// `version`, `planned_change`, `resource_drift`, `outputs` and `diagnostic`
+/// messages. The stream does
+/// not include attribute values, so changes render without them.
+pub fn parse_plan_stream(text: &str) -> Result<TerraformPlan, String> {
+    let mut plan = TerraformPlan::default();
+    for (index, line) in text.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let message: Message =
+            serde_json::from_str(line).map_err(|e| format!("line {}: {}", index + 1, e))?;
+        match (message.kind.as_str(), message.change) {
+            ("planned_change", Some(change)) => plan.resource_changes.push(change.into()),
+            ("resource_drift", Some(change)) => plan.resource_drift.push(change.into()),
+            ("version", _) => {
+                plan.metadata.terraform_version = message.terraform;
+                plan.metadata.timestamp = message.timestamp;
+            }
+            ("diagnostic", _) => plan.diagnostics.extend(message.diagnostic),
+            ("outputs", _) => {
+                for (name, output) in message.outputs {
+                    plan.output_changes.insert(
+                        name,
+                        Change {
+                            actions: actions(&output.action),
+                            after_sensitive: output.sensitive.into(),
+                            ..Default::default()
+                        },
+                    );
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(plan)
+}
+
+impl From<StreamChange> for ResourceChange {
+    fn from(change: StreamChange) -> Self {
+        let resource = change.resource;
+        let mode = if resource.resource.starts_with("data.") {
+            "data"
+        } else {
+            "managed"
+        };
+        ResourceChange {
+            address: resource.addr,
+            mode: mode.to_string(),
+            resource_type: resource.resource_type,
+            name: resource.resource_name,
+            provider_name: resource.implied_provider,
+            module_address: Some(resource.module).filter(|module| !module.is_empty()),
+            previous_address: change.previous_resource.map(|previous| previous.addr),
+            action_reason: change.reason.map(|reason| action_reason(&reason)),
+            change: Change {
+                actions: actions(&change.action),
+                importing: change.importing,
+                generated_config: change.generated_config,
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+}
+
+/// Translates a stream action into the action list of the plan document.
+pub(crate) fn actions(action: &str) -> Vec<String> {
+    let actions: &[&str] = match action {
+        "replace" => &["delete", "create"],
+        "noop" | "move" | "import" => &["no-op"],
+        "remove" => &["forget"],
+        other => &[other],
+    };
+    actions.iter().map(|action| action.to_string()).collect()
+}
+
+/// The stream shortens some reasons; this restores the plan document's names.
+fn action_reason(reason: &str) -> String {
+    match reason {
+        "tainted" => "replace_because_tainted",
+        "requested" => "replace_by_request",
+        "cannot_update" => "replace_because_cannot_update",
+        other => other,
+    }
+    .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{format_plan, get_action};
+
+    const STREAM: &str = r#"{"@level":"info","@message":"Terraform 1.9.0","type":"version","terraform":"1.9.0","ui":"1.2"}
+{"@level":"info","@message":"aws_instance.web: Drift detected (update)","type":"resource_drift","change":{"resource":{"addr":"aws_instance.web","module":"","resource":"aws_instance.web","implied_provider":"aws","resource_type":"aws_instance","resource_name":"web","resource_key":null},"action":"update"}}
+{"@level":"info","@message":"aws_instance.web: Plan to replace","type":"planned_change","change":{"resource":{"addr":"aws_instance.web","module":"","resource":"aws_instance.web","implied_provider":"aws","resource_type":"aws_instance","resource_name":"web","resource_key":null},"action":"replace","reason":"tainted"}}
+{"@level":"info","@message":"module.net.data.aws_vpc.main: Plan to read","type":"planned_change","change":{"resource":{"addr":"module.net.data.aws_vpc.main","module":"module.net","resource":"data.aws_vpc.main","implied_provider":"aws","resource_type":"aws_vpc","resource_name":"main","resource_key":null},"action":"read","reason":"read_because_config_unknown"}}
+{"@level":"info","@message":"aws_s3_bucket.new: Plan to move","type":"planned_change","change":{"resource":{"addr":"aws_s3_bucket.new","module":"","resource":"aws_s3_bucket.new","implied_provider":"aws","resource_type":"aws_s3_bucket","resource_name":"new","resource_key":null},"previous_resource":{"addr":"aws_s3_bucket.old","module":"","resource":"aws_s3_bucket.old","implied_provider":"aws","resource_type":"aws_s3_bucket","resource_name":"old","resource_key":null},"action":"move"}}
+{"@level":"info","@message":"Plan: 1 to add, 0 to change, 1 to destroy.","type":"change_summary","changes":{"add":1,"change":0,"import":0,"remove":1,"operation":"plan"}}
+{"@level":"info","@message":"Outputs: 2","type":"outputs","outputs":{"ip":{"sensitive":false,"action":"create"},"token":{"sensitive":true,"action":"update"}}}
+"#;
+
+    #[test]
+    fn test_parse_plan_stream() {
+        assert!(is_plan_stream(STREAM));
+        assert!(!is_plan_stream(r#"{"format_version":"1.2"}"#));
+
+        let plan = parse_plan_stream(STREAM).unwrap();
+        assert_eq!(plan.resource_changes.len(), 3);
+        let web = &plan.resource_changes[0];
+        assert_eq!(get_action(&web.change.actions), "replace");
+        assert_eq!(
+            web.action_reason.as_deref(),
+            Some("replace_because_tainted")
+        );
+        let read = &plan.resource_changes[1];
+        assert_eq!(read.mode, "data");
+        assert_eq!(read.module_address.as_deref(), Some("module.net"));
+        assert_eq!(
+            plan.resource_changes[2].display_address(),
+            "aws_s3_bucket.old -> aws_s3_bucket.new"
+        );
+
+        let output = format_plan(&plan, true);
+        assert!(output.contains(
+            "Objects changed outside of Terraform:\n    ~ aws_instance.web has changed\n"
+        ));
+        assert!(output.contains("<= module.net.data.aws_vpc.main (config refers to values"));
+        assert!(output.contains("Changes to Outputs:\n    + ip\n    ~ token = (sensitive value)\n"));
+        assert!(output.contains("Plan: 0 to add, 1 to change, 0 to destroy.\n"));
+    }
+
+    #[test]
+    fn test_invalid_stream_line() {
+        let error = parse_plan_stream("{\"@message\":\"ok\"}\nnot json\n").unwrap_err();
+        assert!(error.starts_with("line 2: "));
+    }
+}
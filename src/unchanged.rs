@@ -0,0 +1,158 @@
+use crate::{get_action, Change};
+use serde_json::Value;
+
+/// Attributes and nested blocks an update leaves untouched, which Terraform
+/// summarises as `# (N unchanged attributes hidden)`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Unchanged {
+    /// Unchanged attributes in plan order, including those holding blocks.
+    pub attributes: Vec<(String, Value)>,
+}
+
+impl Unchanged {
+    /// Collects the unchanged values of an update or replace. Other actions
+    /// show every attribute, so nothing is hidden. Nulls and empty lists are
+    /// skipped, as Terraform does not render them either.
+    pub fn from_change(change: &Change) -> Self {
+        let action = get_action(&change.actions);
+        if action != "update" && action != "replace" {
+            return Self::default();
+        }
+        let (Some(before), Some(after)) = (
+            change.before.as_ref().and_then(Value::as_object),
+            change.after.as_ref().and_then(Value::as_object),
+        ) else {
+            return Self::default();
+        };
+
+        let attributes = after
+            .iter()
+            .filter(|(key, value)| before.get(*key) == Some(*value))
+            .filter(|(_, value)| !is_blank(value))
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect();
+        Unchanged { attributes }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.attributes.is_empty()
+    }
+
+    /// Number of unchanged nested blocks: every element of a list of objects.
+    pub fn block_count(&self) -> usize {
+        self.attributes
+            .iter()
+            .filter_map(|(_, value)| block_list(value))
+            .map(|blocks| blocks.len())
+            .sum()
+    }
+
+    /// Number of unchanged attributes that are not nested blocks.
+    pub fn attribute_count(&self) -> usize {
+        self.attributes
+            .iter()
+            .filter(|(_, value)| block_list(value).is_none())
+            .count()
+    }
+
+    /// `# (N unchanged attributes hidden)` and `# (N unchanged blocks hidden)`,
+    /// omitting zero counts.
+    pub fn format_counts(&self) -> Vec<String> {
+        [
+            (self.attribute_count(), "attribute"),
+            (self.block_count(), "block"),
+        ]
+        .into_iter()
+        .filter(|(count, _)| *count > 0)
+        .map(|(count, noun)| {
+            format!(
+                "# ({} unchanged {}{} hidden)",
+                count,
+                noun,
+                if count == 1 { "" } else { "s" }
+            )
+        })
+        .collect()
+    }
+}
+
+pub(crate) fn is_blank(value: &Value) -> bool {
+    match value {
+        Value::Null => true,
+        Value::Array(items) => items.is_empty(),
+        _ => false,
+    }
+}
+
+/// Nested blocks appear in plan JSON as lists of objects.
+pub(crate) fn block_list(value: &Value) -> Option<&Vec<Value>> {
+    value
+        .as_array()
+        .filter(|items| !items.is_empty() && items.iter().all(Value::is_object))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn change(actions: &[&str], before: Value, after: Value) -> Change {
+        Change {
+            actions: actions.iter().map(|a| a.to_string()).collect(),
+            before: Some(before),
+            after: Some(after),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_counts_attributes_and_blocks() {
+        let unchanged = Unchanged::from_change(&change(
+            &["update"],
+            json!({
+                "ami": "ami-1",
+                "instance_type": "t3.micro",
+                "tags": {"Name": "web"},
+                "ebs_block_device": [{"device_name": "/dev/sdb"}, {"device_name": "/dev/sdc"}],
+                "root_block_device": [{"volume_size": 8}],
+                "user_data": null,
+                "security_groups": []
+            }),
+            json!({
+                "ami": "ami-2",
+                "instance_type": "t3.micro",
+                "tags": {"Name": "web"},
+                "ebs_block_device": [{"device_name": "/dev/sdb"}, {"device_name": "/dev/sdc"}],
+                "root_block_device": [{"volume_size": 8}],
+                "user_data": null,
+                "security_groups": []
+            }),
+        ));
+
+        assert_eq!(unchanged.attribute_count(), 2);
+        assert_eq!(unchanged.block_count(), 3);
+        assert_eq!(
+            unchanged.format_counts(),
+            vec![
+                "# (2 unchanged attributes hidden)",
+                "# (3 unchanged blocks hidden)"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_only_updates_hide_attributes() {
+        let create = change(&["create"], Value::Null, json!({"ami": "ami-1"}));
+        assert!(Unchanged::from_change(&create).is_empty());
+
+        let update = change(
+            &["update"],
+            json!({"a": 1, "b": 2}),
+            json!({"a": 1, "b": 3}),
+        );
+        assert_eq!(
+            Unchanged::from_change(&update).format_counts(),
+            vec!["# (1 unchanged attribute hidden)"]
+        );
+    }
+}
@@ -0,0 +1,102 @@
+use crate::{format_value, TerraformPlan, SENSITIVE_PLACEHOLDER};
+use serde::Deserialize;
+use serde_json::Value;
+
+/// An entry of the plan's `variables` map.
+#[derive(Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct PlanVariable {
+    #[serde(default)]
+    pub value: Value,
+}
+
+/// A root module input variable and the value the plan was made with.
+#[derive(Debug, Clone, PartialEq)]
+pub struct InputVariable {
+    pub name: String,
+    pub value: Value,
+    /// Declared `sensitive = true`; the value must not be shown.
+    pub sensitive: bool,
+}
+
+impl InputVariable {
+    /// The value as shown to reviewers, masked if sensitive.
+    pub fn display_value(&self) -> String {
+        if self.sensitive {
+            SENSITIVE_PLACEHOLDER.to_string()
+        } else {
+            format_value(&self.value)
+        }
+    }
+
+    /// `region = "eu-west-1"`
+    pub fn format(&self) -> String {
+        format!("{} = {}", self.name, self.display_value())
+    }
+}
+
+impl TerraformPlan {
+    /// The input variables in name order, with sensitivity taken from the
+    /// root module's variable declarations.
+    pub fn input_variables(&self) -> Vec<InputVariable> {
+        let declarations = &self.configuration.root_module.variables;
+        self.variables
+            .iter()
+            .map(|(name, variable)| InputVariable {
+                name: name.clone(),
+                value: variable.value.clone(),
+                sensitive: declarations
+                    .get(name)
+                    .is_some_and(|declaration| declaration.sensitive),
+            })
+            .collect()
+    }
+}
+
+/// `▼ Input variables (2):` and one line per variable, or only the heading
+/// when collapsed.
+pub(crate) fn format_variables(variables: &[InputVariable], collapsed: bool) -> String {
+    if variables.is_empty() {
+        return String::new();
+    }
+    let indicator = if collapsed { "▶" } else { "▼" };
+    let mut output = format!("{} Input variables ({})\n", indicator, variables.len());
+    if !collapsed {
+        for variable in variables {
+            output.push_str(&format!("    {}\n", variable.format()));
+        }
+    }
+    output.push('\n');
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_sensitive_variables_are_masked() {
+        let plan: TerraformPlan = serde_json::from_value(json!({
+            "resource_changes": [],
+            "variables": {
+                "region": {"value": "eu-west-1"},
+                "db_password": {"value": "hunter2"}
+            },
+            "configuration": {"root_module": {"variables": {
+                "region": {},
+                "db_password": {"sensitive": true}
+            }}}
+        }))
+        .unwrap();
+
+        let variables = plan.input_variables();
+        assert_eq!(
+            format_variables(&variables, false),
+            "▼ Input variables (2)\n    db_password = (sensitive value)\n    region = \"eu-west-1\"\n\n"
+        );
+        assert_eq!(
+            format_variables(&variables, true),
+            "▶ Input variables (2)\n\n"
+        );
+    }
+}
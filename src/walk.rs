@@ -0,0 +1,185 @@
+use crate::{
+    action_symbol, drift_for, get_action, sort_changes, Annotations, ExpressionSources,
+    FormatOptions, ResourceChange, RiskLevel, SchemaBlock, SortOrder, TerraformPlan,
+};
+use std::cmp::Reverse;
+use std::collections::HashMap;
+
+/// A resource change with what every output shows alongside it.
+#[derive(Debug, Clone)]
+pub struct ChangeView<'a> {
+    pub change: &'a ResourceChange,
+    /// The action as returned by [`get_action`].
+    pub action: &'static str,
+    /// `+`, `~`, `-` or `-/+` for the action, `←` for an import and `→` for
+    /// a move with no other action.
+    pub symbol: &'static str,
+    pub risk: RiskLevel,
+    drift: Option<ResourceChange>,
+    schema: Option<&'a SchemaBlock>,
+    sources: Option<ExpressionSources>,
+}
+
+impl ChangeView<'_> {
+    pub fn annotations(&self) -> Annotations<'_> {
+        Annotations {
+            drift: self.drift.as_ref(),
+            schema: self.schema,
+            sources: self.sources.as_ref(),
+        }
+    }
+}
+
+/// The resource changes of a prepared plan in the order `options.sort` asks
+/// for, with data reads set aside and the actions counted for the summary.
+/// With [`SortOrder::Risk`], critical and then high-risk changes come first.
+#[derive(Debug, Clone, Default)]
+pub struct PlanChanges<'a> {
+    pub changes: Vec<ChangeView<'a>>,
+    pub reads: Vec<&'a ResourceChange>,
+    /// Changes per action, and `import` for the changes that import.
+    pub counts: HashMap<&'static str, i32>,
+}
+
+impl<'a> PlanChanges<'a> {
+    pub fn new(plan: &'a TerraformPlan, options: &'a FormatOptions) -> Self {
+        let drift = plan.relevant_drift();
+        let mut walk = PlanChanges::default();
+        for change in sort_changes(&plan.resource_changes, options.sort) {
+            if change.is_data_read() {
+                walk.reads.push(change);
+                continue;
+            }
+            let action = get_action(&change.change.actions);
+            *walk.counts.entry(action).or_insert(0) += 1;
+            if change.is_importing() {
+                *walk.counts.entry("import").or_insert(0) += 1;
+            }
+            walk.changes.push(ChangeView {
+                change,
+                action,
+                symbol: change_symbol(change),
+                risk: options.risk.classify(change),
+                drift: drift_for(&drift, &change.address).cloned(),
+                schema: options
+                    .schema
+                    .as_ref()
+                    .and_then(|schema| schema.block(change)),
+                sources: plan.configuration.sources(change),
+            });
+        }
+        if options.sort == SortOrder::Risk {
+            walk.changes
+                .sort_by_key(|view| Reverse(view.risk.max(RiskLevel::Medium)));
+        }
+        walk
+    }
+}
+
+/// The marker for a change: its action's, or `←`/`→` for an import or a
+/// move that changes nothing else.
+pub(crate) fn change_symbol(change: &ResourceChange) -> &'static str {
+    match get_action(&change.change.actions) {
+        action @ ("create" | "update" | "delete" | "replace") => action_symbol(action),
+        _ if change.is_importing() => "←",
+        _ if change.is_moved() => "→",
+        _ => "?",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_plan_changes() {
+        let plan: TerraformPlan = serde_json::from_value(json!({
+            "resource_changes": [
+                {
+                    "address": "aws_instance.web",
+                    "type": "aws_instance",
+                    "change": {"actions": ["delete", "create"], "before": {}, "after": {}}
+                },
+                {
+                    "address": "data.aws_ami.latest",
+                    "mode": "data",
+                    "type": "aws_ami",
+                    "change": {"actions": ["read"]}
+                },
+                {
+                    "address": "aws_s3_bucket.logs",
+                    "type": "aws_s3_bucket",
+                    "change": {"actions": ["no-op"], "importing": {"id": "logs"}}
+                }
+            ],
+            "resource_drift": [{
+                "address": "aws_instance.web",
+                "type": "aws_instance",
+                "change": {"actions": ["update"], "before": {"ami": "a"}, "after": {"ami": "b"}}
+            }]
+        }))
+        .unwrap();
+        let options = FormatOptions::default();
+
+        let walk = PlanChanges::new(&plan, &options);
+
+        let symbols: Vec<&str> = walk.changes.iter().map(|view| view.symbol).collect();
+        assert_eq!(symbols, ["-/+", "←"]);
+        assert_eq!(walk.reads.len(), 1);
+        assert_eq!(walk.counts.get("replace"), Some(&1));
+        assert_eq!(walk.counts.get("import"), Some(&1));
+        assert!(walk.changes[0].annotations().drift.is_some());
+        assert!(walk.changes[1].annotations().drift.is_none());
+    }
+
+    #[test]
+    fn test_high_risk_changes_first() {
+        let change = |address: &str, actions: &[&str]| {
+            json!({
+                "address": address,
+                "type": address.split('.').next(),
+                "change": {"actions": actions, "before": {}, "after": {}}
+            })
+        };
+        let plan: TerraformPlan = serde_json::from_value(json!({
+            "resource_changes": [
+                change("aws_instance.a", &["update"]),
+                change("aws_iam_role.b", &["update"]),
+                change("aws_instance.c", &["create"]),
+                change("aws_db_instance.d", &["delete"]),
+            ]
+        }))
+        .unwrap();
+        let addresses = |options: &FormatOptions| -> Vec<String> {
+            PlanChanges::new(&plan, options)
+                .changes
+                .iter()
+                .map(|view| view.change.address.clone())
+                .collect()
+        };
+
+        assert_eq!(
+            addresses(&FormatOptions::default()),
+            [
+                "aws_db_instance.d",
+                "aws_iam_role.b",
+                "aws_instance.a",
+                "aws_instance.c"
+            ]
+        );
+        let options = FormatOptions {
+            sort: SortOrder::Plan,
+            ..Default::default()
+        };
+        assert_eq!(
+            addresses(&options),
+            [
+                "aws_instance.a",
+                "aws_iam_role.b",
+                "aws_instance.c",
+                "aws_db_instance.d"
+            ]
+        );
+    }
+}
@@ -0,0 +1,364 @@
+use serde_json::{json, Value};
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::{Command, Output, Stdio};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+fn fixture(name: &str) -> String {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join(name)
+        .display()
+        .to_string()
+}
+
+/// Runs the binary without picking up a configuration file from the
+/// developer's machine.
+fn tfplan(args: &[&str]) -> Command {
+    let mut command = Command::new(env!("CARGO_BIN_EXE_tfplan"));
+    command
+        .args(args)
+        .current_dir(env!("CARGO_MANIFEST_DIR"))
+        .env(
+            "XDG_CONFIG_HOME",
+            std::env::temp_dir().join("tfplan-no-config"),
+        );
+    command
+}
+
+/// A copy of the `plan.json` fixture edited by a test, removed when dropped.
+struct EditedPlan(PathBuf);
+
+impl EditedPlan {
+    fn new(edit: impl FnOnce(&mut Value)) -> Self {
+        static COUNT: AtomicUsize = AtomicUsize::new(0);
+        let mut plan: Value =
+            serde_json::from_str(&std::fs::read_to_string(fixture("plan.json")).unwrap()).unwrap();
+        edit(&mut plan);
+        let path = std::env::temp_dir().join(format!(
+            "tfplan-{}-{}.json",
+            std::process::id(),
+            COUNT.fetch_add(1, Ordering::Relaxed)
+        ));
+        std::fs::write(&path, plan.to_string()).unwrap();
+        EditedPlan(path)
+    }
+
+    fn path(&self) -> &str {
+        self.0.to_str().unwrap()
+    }
+}
+
+impl Drop for EditedPlan {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.0);
+    }
+}
+
+fn run(args: &[&str]) -> Output {
+    tfplan(args).output().unwrap()
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+#[test]
+fn test_default_invocation_renders() {
+    let plan = fixture("plan.json");
+    let default = run(&[&plan]);
+    let render = run(&["render", &plan]);
+
+    assert!(default.status.success());
+    assert_eq!(stdout(&default), stdout(&render));
+    assert!(stdout(&default).contains("▼ ~ aws_instance.web will be update\n"));
+    let critical = stdout(&default).find("▼ - aws_s3_bucket.logs").unwrap();
+    assert!(critical < stdout(&default).find("▼ ~ aws_instance.web").unwrap());
+    assert!(stdout(&default).contains("Plan: 1 to add, 1 to change, 1 to destroy.\n"));
+}
+
+#[test]
+fn test_reads_stdin() {
+    let mut child = tfplan(&["summary"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let plan = std::fs::read(fixture("plan.json")).unwrap();
+    child.stdin.take().unwrap().write_all(&plan).unwrap();
+    let output = child.wait_with_output().unwrap();
+
+    assert_eq!(
+        stdout(&output),
+        "Terraform 1.6.0 · format 1.2\n\n⚠ High-risk changes:\n    [CRITICAL] aws_s3_bucket.logs\n\n\
+         Plan: 1 to add, 1 to change, 1 to destroy.\nRisk: critical (score 19)\n"
+    );
+}
+
+#[test]
+fn test_output_formats() {
+    let plan = fixture("plan.json");
+    let html = run(&["--html", &plan]);
+    assert!(stdout(&html).starts_with("<!DOCTYPE html>"));
+    assert_eq!(
+        stdout(&html),
+        stdout(&run(&["render", "--format", "html", &plan]))
+    );
+
+    let markdown = stdout(&run(&["--format", "markdown", &plan]));
+    assert!(markdown.starts_with("### Terraform plan\n"));
+
+    let hcl = run(&["render", &plan, "--style", "hcl"]);
+    assert!(stdout(&hcl).contains("  ~ resource \"aws_instance\" \"web\" {\n"));
+}
+
+#[test]
+fn test_stats_replace_the_summary_line() {
+    let output = stdout(&run(&["--stats", &fixture("plan.json")]));
+
+    assert!(output.contains("▼ ~ aws_instance.web will be update\n"));
+    assert!(output.contains("\n\nResource type     add  change  replace  destroy\n"));
+    assert_eq!(output.matches("Plan: 1 to add").count(), 1);
+}
+
+#[test]
+fn test_conflicting_formats_are_rejected() {
+    let output = run(&["--html", "--interactive", &fixture("plan.json")]);
+
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("cannot be used with"));
+}
+
+#[test]
+fn test_check_exit_code() {
+    let plan = fixture("plan.json");
+    let failing = run(&["check", &plan]);
+    assert_eq!(failing.status.code(), Some(1));
+    assert!(stdout(&failing).contains("[CRITICAL] aws_s3_bucket.logs"));
+
+    let passing = run(&["check", &plan, "--risk-rule", "aws_s3_bucket=low"]);
+    assert!(passing.status.success());
+}
+
+#[test]
+fn test_diff() {
+    let output = run(&["diff", &fixture("plan.json"), &fixture("plan-rebased.json")]);
+
+    assert!(output.status.success());
+    assert_eq!(
+        stdout(&output),
+        "~ aws_instance.web: planned values changed (ami)\n\
+         - aws_s3_bucket.logs (delete, only in old plan)\n\
+         \n2 differences between the plans.\n"
+    );
+}
+
+#[test]
+fn test_config_show() {
+    let dir = std::env::temp_dir().join(format!("tfplan-cli-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let config = dir.join("tfplan.toml");
+    std::fs::write(
+        &config,
+        "sort = \"action\"\nstyle = \"hcl\"\ncollapsed = true\n",
+    )
+    .unwrap();
+    let config = config.display().to_string();
+
+    let output = run(&[
+        "config",
+        "show",
+        "--config",
+        &config,
+        "--style",
+        "compact",
+        "--no-collapsed",
+    ]);
+    let shown = stdout(&output);
+    assert!(shown.starts_with(&format!("# Configuration file: {}\n", config)));
+    assert!(shown.contains("sort = \"action\"\n"));
+    assert!(shown.contains("style = \"compact\"\n"));
+    assert!(shown.contains("collapsed = false\n"));
+
+    let shown = stdout(&run(&[
+        "config",
+        "show",
+        "--config",
+        &config,
+        "--no-collapsed",
+        "--collapsed",
+    ]));
+    assert!(shown.contains("collapsed = true\n"));
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_plan_stream() {
+    let output = run(&["summary", &fixture("plan-stream.jsonl")]);
+
+    assert!(output.status.success());
+    assert_eq!(
+        stdout(&output),
+        "Terraform 1.9.0\n\n⚠ High-risk changes:\n    [CRITICAL] aws_db_instance.main\n\n\
+         Plan: 0 to add, 1 to change, 1 to destroy.\nRisk: critical (score 16)\n"
+    );
+}
+
+#[test]
+fn test_plan_metadata_warnings() {
+    let plan = EditedPlan::new(|plan| {
+        plan["timestamp"] = "2020-01-01T00:00:00Z".into();
+        plan["applyable"] = false.into();
+    });
+    let path = plan.path();
+
+    let output = stdout(&run(&["-c", "--max-age", "7d", path]));
+    assert!(output.starts_with("Terraform 1.6.0 · format 1.2 · 2020-01-01T00:00:00Z ("));
+    assert!(output.contains("\n⚠ This plan is not applyable: Terraform will refuse to apply it.\n"));
+    assert!(output.contains(" old, older than the maximum age of 7d.\n"));
+}
+
+#[test]
+fn test_errors_fail_the_command() {
+    let output = run(&[&fixture("plan-stream-errored.jsonl")]);
+
+    assert_eq!(output.status.code(), Some(1));
+    assert!(stdout(&output).contains(
+        "Error: Reference to undeclared input variable\n\n  \
+         on main.tf line 12, in resource \"aws_instance\" \"web\":\n  \
+         12:   ami = var.amii\n              ^^^^^^^^\n"
+    ));
+    assert!(stdout(&output).contains("Warning: Argument is deprecated\n"));
+}
+
+#[test]
+fn test_apply_report() {
+    let output = run(&["apply", &fixture("apply.jsonl")]);
+
+    assert_eq!(output.status.code(), Some(1));
+    assert!(stdout(&output).ends_with(
+        "+ aws_instance.web: created after 12s [id=i-0abc]\n\
+         - aws_s3_bucket.logs: failed after 2s\n\
+         \nError: deleting S3 Bucket (logs): BucketNotEmpty\n\n  with aws_s3_bucket.logs,\n\
+         \nApply report: 1 done, 1 failed, 0 not finished.\n"
+    ));
+}
+
+#[test]
+fn test_fail_on_checks() {
+    let plan = EditedPlan::new(|plan| {
+        plan["checks"] = json!([{
+            "address": {"kind": "check", "to_display": "check.health"},
+            "status": "fail",
+            "instances": [{
+                "address": {"to_display": "check.health"},
+                "status": "fail",
+                "problems": [{"message": "Health endpoint returned 503."}]
+            }]
+        }]);
+    });
+    let path = plan.path();
+
+    let output = run(&["-c", path]);
+    assert!(output.status.success());
+    assert!(stdout(&output).contains(
+        "Checks: 1 failed\n    ✗ check.health failed\n        Health endpoint returned 503.\n"
+    ));
+    assert_eq!(
+        run(&["-c", "--fail-on-checks", path]).status.code(),
+        Some(1)
+    );
+}
+
+#[test]
+fn test_relevant_drift() {
+    let plan = EditedPlan::new(|plan| {
+        plan["resource_drift"] = json!([
+            {
+                "address": "aws_instance.web",
+                "change": {"actions": ["update"], "before": {"ami": "b", "cpu": 1}, "after": {"ami": "c", "cpu": 2}}
+            },
+            {
+                "address": "aws_iam_role.app",
+                "change": {"actions": ["update"], "before": {"name": "a"}, "after": {"name": "b"}}
+            }
+        ]);
+        plan["relevant_attributes"] =
+            json!([{"resource": "aws_instance.web", "attribute": ["ami"]}]);
+    });
+    let path = plan.path();
+
+    let output = stdout(&run(&["-c", path]));
+    assert!(output.contains(
+        "Objects changed outside of Terraform:\n    ~ aws_instance.web has changed (ami)\n\n"
+    ));
+    assert!(
+        output.contains("▶ ~ aws_instance.web will be update (ami changed outside of Terraform)\n")
+    );
+
+    let output = stdout(&run(&["-c", "--all-drift", path]));
+    assert!(output.contains("    ~ aws_instance.web has changed (ami, cpu)\n"));
+    assert!(output.contains("    ~ aws_iam_role.app has changed (name)\n"));
+}
+
+#[test]
+fn test_provider_schema() {
+    let plan = EditedPlan::new(|plan| {
+        plan["resource_changes"][0]["change"]["after"]["cpu_core_count"] = 4.into();
+    });
+    let path = plan.path();
+    let schema = fixture("schema.json");
+
+    let output = stdout(&run(&["--schema", &schema, path]));
+    assert!(output.contains("        cpu_core_count: 4 # deprecated\n"));
+    assert!(output.contains(
+        "Warning: Deprecated attribute\n\n  with aws_instance.web,\n\n\
+         \"cpu_core_count\" is deprecated in the aws_instance schema.\n"
+    ));
+
+    let html = stdout(&run(&["--html", "--schema", &schema, path]));
+    assert!(html.contains(r#"<span class="key" title="AMI to use for the instance.">ami:</span>"#));
+}
+
+#[test]
+fn test_expression_sources() {
+    let plan = EditedPlan::new(|plan| {
+        plan["configuration"] = json!({
+            "root_module": {
+                "resources": [{
+                    "address": "aws_instance.web",
+                    "mode": "managed",
+                    "type": "aws_instance",
+                    "name": "web",
+                    "expressions": {"ami": {"references": ["var.ami"]}}
+                }]
+            }
+        });
+    });
+    let path = plan.path();
+
+    let output = stdout(&run(&[path]));
+    assert!(output.contains("        ami: \"a\" => \"b\" <- var.ami\n"));
+
+    let html = stdout(&run(&["--html", path]));
+    assert!(html.contains(r#"<span class="expression-source">&lt;- var.ami</span>"#));
+}
+
+#[test]
+fn test_generated_config_is_opt_in() {
+    let plan = EditedPlan::new(|plan| {
+        plan["resource_changes"][0]["change"]["generated_config"] =
+            "resource \"aws_instance\" \"web\" {}".into();
+    });
+    let path = plan.path();
+
+    for args in [&["-c", path][..], &["--html", path]] {
+        assert!(!stdout(&run(args)).contains("generated configuration"));
+    }
+    let output = stdout(&run(&["--generated-config", path]));
+    assert!(output.contains("resource \"aws_instance\" \"web\" {}"));
+    let html = stdout(&run(&["--html", "--generated-config", path]));
+    assert!(html.contains(r#"<pre class="generated-config">resource &quot;aws_instance&quot;"#));
+}